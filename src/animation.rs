@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use crate::render::{self, Canvas};
+
+/// Somewhere a simulation can send snapshots of its state as it progresses.
+///
+/// Frames are passed as closures so that drawing them costs nothing when they're
+/// discarded.
+pub trait Recorder {
+    fn record(&mut self, frame: impl FnOnce() -> Canvas);
+}
+
+/// A recorder which throws every frame away, for running simulations normally.
+pub struct Discard;
+
+impl Recorder for Discard {
+    fn record(&mut self, _frame: impl FnOnce() -> Canvas) {}
+}
+
+/// A recorder which keeps frames to export once the simulation has finished.
+#[derive(Debug)]
+pub struct Recording {
+    frames: Vec<Canvas>,
+    every: usize,
+    limit: usize,
+    offered: usize,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self::new(1, usize::MAX)
+    }
+}
+
+impl Recorder for Recording {
+    fn record(&mut self, frame: impl FnOnce() -> Canvas) {
+        if self.offered.is_multiple_of(self.every) && self.frames.len() < self.limit {
+            self.frames.push(frame());
+        }
+        self.offered += 1;
+    }
+}
+
+impl Recording {
+    /// Keep only every `every`th frame, starting with the first, and at most `limit` of
+    /// them, to bound the memory long simulations use.
+    pub fn new(every: usize, limit: usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            every: every.max(1),
+            limit,
+            offered: 0,
+        }
+    }
+
+    pub fn frames(&self) -> &[Canvas] {
+        &self.frames
+    }
+
+    /// Every frame as plain text, each preceded by a line naming it.
+    pub fn text(&self) -> String {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| format!("Frame {}\n{}", index + 1, render::plain(frame)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Every frame as an animated PNG which loops forever, each cell a `scale` pixel
+    /// square and each frame shown for `delay`. Fails if nothing was recorded.
+    pub fn apng(&self, scale: usize, delay: Duration) -> Result<Vec<u8>, png::EncodingError> {
+        let (width, height) = self.frames.first().map_or((0, 0), |first| {
+            (first.width() * scale.max(1), first.height() * scale.max(1))
+        });
+
+        let mut image = Vec::new();
+        let mut encoder = png::Encoder::new(&mut image, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        encoder.set_frame_delay(delay.as_millis().min(u16::MAX as u128) as u16, 1000)?;
+
+        let mut writer = encoder.write_header()?;
+        for frame in &self.frames {
+            writer.write_image_data(&render::pixels(frame, scale).2)?;
+        }
+        writer.finish()?;
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::Grid,
+        render::{Colour, Glyph},
+    };
+
+    fn frame(symbol: char) -> Canvas {
+        Grid::new(2, 1, vec![Glyph::new(symbol, Colour::WHITE); 2])
+    }
+
+    #[test]
+    fn sampling() {
+        let mut recording = Recording::new(2, 2);
+        let mut drawn = 0;
+        for symbol in ['a', 'b', 'c', 'd', 'e'] {
+            recording.record(|| {
+                drawn += 1;
+                frame(symbol)
+            });
+        }
+
+        assert_eq!(drawn, 2);
+        assert_eq!(recording.text(), "Frame 1\naa\n\nFrame 2\ncc\n");
+    }
+
+    #[test]
+    fn animated() {
+        let mut recording = Recording::default();
+        assert!(recording.apng(1, Duration::from_millis(100)).is_err());
+
+        recording.record(|| frame('a'));
+        recording.record(|| frame('b'));
+        let image = recording.apng(2, Duration::from_millis(100)).unwrap();
+
+        let reader = png::Decoder::new(image.as_slice()).read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (4, 2));
+        assert_eq!(info.animation_control.unwrap().num_frames, 2);
+    }
+}
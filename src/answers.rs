@@ -0,0 +1,265 @@
+use std::{
+    collections::BTreeMap, convert::Infallible, error::Error, fmt, fs, io, path::PathBuf,
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::parser;
+
+/// A puzzle answer.
+///
+/// Numbers are always held in the narrowest variant that fits them, so answers compare
+/// equal however they were produced, whether solved, typed in or read back from a file.
+/// Text is only taken for a number when parsed, and then only when written exactly as the
+/// number would be, so an answer such as `007` stays as it was given.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Answer {
+    Integer(i64),
+    /// An integer too large for `Integer`.
+    Big(i128),
+    /// Anything that isn't a number, such as a word or a comma-separated list.
+    Text(String),
+}
+
+impl Answer {
+    /// The answer as a number to compare with others, if it is one.
+    pub fn number(&self) -> Option<i128> {
+        match self {
+            Answer::Integer(value) => Some(*value as i128),
+            Answer::Big(value) => Some(*value),
+            Answer::Text(_) => None,
+        }
+    }
+
+    /// Join several values into a single comma-separated answer.
+    pub fn list<T: fmt::Display>(values: impl IntoIterator<Item = T>) -> Self {
+        values
+            .into_iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+            .into()
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::Big(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(value: i128) -> Self {
+        i64::try_from(value).map_or(Answer::Big(value), Answer::Integer)
+    }
+}
+
+macro_rules! integer_answer {
+    ($($integer:ty),+) => {
+        $(impl From<$integer> for Answer {
+            fn from(value: $integer) -> Self {
+                Answer::from(value as i128)
+            }
+        })+
+    };
+}
+
+integer_answer!(i32, i64, isize, u32, u64, usize);
+
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text.parse::<i128>() {
+            Ok(value) if value.to_string() == text => Answer::from(value),
+            _ => Answer::Text(text.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+/// Integers that fit are written as numbers, everything else as a string, which is read
+/// back as text unless it's an integer too large to have been written as a number.
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Integer(value) => serializer.serialize_i64(*value),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Answer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Answer;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an integer or a string")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Answer, E> {
+                Ok(value.into())
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Answer, E> {
+                Ok(value.into())
+            }
+
+            fn visit_i128<E: de::Error>(self, value: i128) -> Result<Answer, E> {
+                Ok(value.into())
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Answer, E> {
+                Ok(match value.parse() {
+                    Ok(big @ Answer::Big(_)) => big,
+                    _ => value.into(),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Known-good answers for a year, keyed by `dayN` then `partN`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    days: BTreeMap<String, BTreeMap<String, Answer>>,
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io(io::Error),
+    Malformed(toml::de::Error),
+    Unwritable(toml::ser::Error),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswersError::Io(error) => write!(f, "unable to access answers: {}", error),
+            AnswersError::Malformed(error) => write!(f, "malformed answers: {}", error),
+            AnswersError::Unwritable(error) => write!(f, "unable to write answers: {}", error),
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+impl Answers {
+    pub fn path(year: usize) -> PathBuf {
+        parser::input_dir()
+            .join(year.to_string())
+            .join("answers.toml")
+    }
+
+    /// Load a year's answers, treating a missing manifest as empty.
+    pub fn load(year: usize) -> Result<Self, AnswersError> {
+        match fs::read_to_string(Self::path(year)) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(AnswersError::Io(error)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        toml::from_str(text)
+            .map(|days| Self { days })
+            .map_err(AnswersError::Malformed)
+    }
+
+    pub fn save(&self, year: usize) -> Result<(), AnswersError> {
+        fs::write(Self::path(year), self.to_toml()?).map_err(AnswersError::Io)
+    }
+
+    pub fn to_toml(&self) -> Result<String, AnswersError> {
+        toml::to_string(&self.days).map_err(AnswersError::Unwritable)
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.days
+            .get(&format!("day{}", day))?
+            .get(&format!("part{}", part))
+    }
+
+    /// Record a confirmed answer, returning the one it replaced.
+    pub fn record(&mut self, day: usize, part: usize, answer: Answer) -> Option<Answer> {
+        self.days
+            .entry(format!("day{}", day))
+            .or_default()
+            .insert(format!("part{}", part), answer)
+    }
+
+    /// Make an empty entry for a day, ready for its answers, unless it has one.
+    pub fn reserve(&mut self, day: usize) {
+        self.days.entry(format!("day{}", day)).or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_variants() {
+        assert_eq!(Answer::from(42usize), Answer::Integer(42));
+        assert_eq!(Answer::from(-7isize), Answer::Integer(-7));
+        assert_eq!(Answer::from(u64::MAX), Answer::Big(u64::MAX as i128));
+        assert_eq!(Answer::from("42"), Answer::Text("42".to_string()));
+        assert_eq!(Answer::from("abc"), Answer::Text("abc".to_string()));
+        assert_eq!(Answer::list([4, 6, 3]), Answer::Text("4,6,3".to_string()));
+        assert_eq!(Answer::list([7]), Answer::Text("7".to_string()));
+
+        assert_eq!("42".parse(), Ok(Answer::Integer(42)));
+        assert_eq!("-7".parse(), Ok(Answer::Integer(-7)));
+        for text in ["007", "+5", "-0", " 1", "1,2"] {
+            assert_eq!(text.parse(), Ok(Answer::Text(text.to_string())));
+        }
+        for answer in [
+            Answer::Integer(-3),
+            Answer::Big(1 << 80),
+            Answer::from("1,2"),
+            Answer::from("007"),
+        ] {
+            assert_eq!(answer.to_string().parse(), Ok(answer));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut answers = Answers::parse("[day1]\npart1 = 11\npart2 = \"022\"\n").unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::Integer(11)));
+        assert_eq!(answers.get(1, 2), Some(&Answer::from("022")));
+        assert_eq!(answers.get(2, 1), None);
+
+        assert_eq!(answers.record(1, 1, 12.into()), Some(Answer::Integer(11)));
+        answers.record(10, 2, Answer::Big(i128::MAX));
+        answers.record(17, 1, Answer::list([4, 6, 3]));
+        answers.record(17, 2, Answer::from("007"));
+        answers.reserve(10);
+        answers.reserve(25);
+        let text = answers.to_toml().unwrap();
+        assert!(text.contains("part1 = 12"));
+        assert!(text.contains("[day25]"));
+        assert_eq!(Answers::parse(&text).unwrap(), answers);
+    }
+}
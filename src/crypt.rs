@@ -0,0 +1,322 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::parser::{self, InputError};
+
+/// Environment variable holding the key inputs are encrypted with.
+pub const KEY_VAR: &str = "AOC_INPUT_KEY";
+
+/// Environment variable naming a file holding the key, used when `AOC_INPUT_KEY` isn't set.
+/// Defaults to a `key` file in the input directory.
+pub const KEY_FILE_VAR: &str = "AOC_INPUT_KEY_FILE";
+
+/// The extension added to an encrypted file's name.
+pub const EXTENSION: &str = "enc";
+
+/// Marks an encrypted file, and which format it's in.
+const MAGIC: &[u8; 8] = b"aocenc1\n";
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+
+#[derive(Debug)]
+pub enum CryptError {
+    /// Not an encrypted file, or one cut short.
+    Malformed,
+    /// Encrypted with another key, or altered since.
+    Mismatched,
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CryptError::Malformed => write!(f, "not an encrypted input"),
+            CryptError::Mismatched => write!(f, "wrong key, or the file has been altered"),
+        }
+    }
+}
+
+impl Error for CryptError {}
+
+/// A symmetric key for inputs at rest. Files are encrypted with ChaCha20-Poly1305, under a
+/// key derived from this one with HKDF-SHA256.
+#[derive(Clone)]
+pub struct Key {
+    cipher: [u8; 32],
+    nonce: [u8; 32],
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Key(..)")
+    }
+}
+
+impl Key {
+    /// A key from any text, ignoring surrounding whitespace. It's only as strong as the
+    /// text is unguessable, so prefer one from [`generate`](Key::generate).
+    pub fn new(text: &str) -> Self {
+        let secret = Hkdf::<Sha256>::new(Some(MAGIC), text.trim().as_bytes());
+        let derive = |purpose: &[u8]| {
+            let mut key = [0; 32];
+            secret
+                .expand(purpose, &mut key)
+                .expect("A key should be short enough to derive");
+            key
+        };
+        Self {
+            cipher: derive(b"cipher"),
+            nonce: derive(b"nonce"),
+        }
+    }
+
+    /// The configured key, from `AOC_INPUT_KEY`, or else the key file, if there is one.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        if let Some(text) = env::var(KEY_VAR)
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+        {
+            return Ok(Some(Self::new(&text)));
+        }
+        match fs::read_to_string(key_path()) {
+            Ok(text) if !text.trim().is_empty() => Ok(Some(Self::new(&text))),
+            Ok(_) => Ok(None),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Text for a new random key, as 64 hex digits.
+    pub fn generate() -> io::Result<String> {
+        let mut bytes = [0; 32];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// Encrypt `plain`. The same text always encrypts the same way under the same key, so
+    /// re-encrypting an unchanged input leaves its file unchanged: its nonce is an HMAC of
+    /// the text, which only ever repeats for the same text.
+    pub fn encrypt(&self, plain: &[u8]) -> Vec<u8> {
+        let mut mac =
+            <Hmac<Sha256> as Mac>::new_from_slice(&self.nonce).expect("HMAC should take any key");
+        mac.update(plain);
+        let digest = mac.finalize().into_bytes();
+        let nonce = Nonce::from_slice(&digest[..NONCE_BYTES]);
+
+        let payload = Payload {
+            msg: plain,
+            aad: MAGIC,
+        };
+        let cipher = ChaCha20Poly1305::new((&self.cipher).into())
+            .encrypt(nonce, payload)
+            .expect("An input should be far shorter than the cipher's limit");
+
+        let mut sealed = MAGIC.to_vec();
+        sealed.extend(nonce);
+        sealed.extend(cipher);
+        sealed
+    }
+
+    /// Decrypt what [`encrypt`](Key::encrypt) made, checking it's unaltered.
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, CryptError> {
+        let body = sealed
+            .strip_prefix(MAGIC)
+            .filter(|body| body.len() >= NONCE_BYTES + TAG_BYTES)
+            .ok_or(CryptError::Malformed)?;
+        let (nonce, cipher) = body.split_at(NONCE_BYTES);
+        let payload = Payload {
+            msg: cipher,
+            aad: MAGIC,
+        };
+        ChaCha20Poly1305::new((&self.cipher).into())
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| CryptError::Mismatched)
+    }
+}
+
+pub fn key_path() -> PathBuf {
+    env::var_os(KEY_FILE_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| parser::input_dir().join("key"))
+}
+
+/// Where the encrypted form of the file at `path` is kept.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Every puzzle input under `dir`, laid out as the input directory, which is plain if
+/// `encrypted` is false.
+fn inputs(dir: &Path, encrypted: bool) -> Result<Vec<PathBuf>, InputError> {
+    let unreadable = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| InputError::Unreadable { path, source }
+    };
+    let suffix = if encrypted { ".txt.enc" } else { ".txt" };
+
+    let mut inputs = Vec::new();
+    for year in fs::read_dir(dir).map_err(unreadable(dir))? {
+        let year = year.map_err(unreadable(dir))?.path();
+        let numbered = |name: Option<&str>| name.is_some_and(|name| name.parse::<usize>().is_ok());
+        if !year.is_dir() || !numbered(year.file_name().and_then(|name| name.to_str())) {
+            continue;
+        }
+        for file in fs::read_dir(&year).map_err(unreadable(&year))? {
+            let file = file.map_err(unreadable(&year))?.path();
+            let day = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("day")?.strip_suffix(suffix));
+            if numbered(day) {
+                inputs.push(file);
+            }
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Write `contents` to `path` by way of a temporary file, so it's never left half written.
+fn replace(path: &Path, contents: &[u8]) -> Result<(), InputError> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    fs::write(&partial, contents)
+        .and_then(|()| fs::rename(&partial, path))
+        .map_err(|source| InputError::Unreadable {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// Encrypt every plain input under `dir`, removing the plain files, and return the paths
+/// written.
+pub fn encrypt_dir(dir: &Path, key: &Key) -> Result<Vec<PathBuf>, InputError> {
+    let mut written = Vec::new();
+    for path in inputs(dir, false)? {
+        let plain = fs::read(&path).map_err(|source| InputError::Unreadable {
+            path: path.clone(),
+            source,
+        })?;
+        let encrypted = encrypted_path(&path);
+        replace(&encrypted, &key.encrypt(&plain))?;
+        fs::remove_file(&path).map_err(|source| InputError::Unreadable { path, source })?;
+        written.push(encrypted);
+    }
+    Ok(written)
+}
+
+/// Decrypt every encrypted input under `dir`, removing the encrypted files, and return the
+/// paths written. Nothing is removed unless it decrypted with `key`.
+pub fn decrypt_dir(dir: &Path, key: &Key) -> Result<Vec<PathBuf>, InputError> {
+    let mut written = Vec::new();
+    for encrypted in inputs(dir, true)? {
+        let sealed = fs::read(&encrypted).map_err(|source| InputError::Unreadable {
+            path: encrypted.clone(),
+            source,
+        })?;
+        let plain = key
+            .decrypt(&sealed)
+            .map_err(|source| InputError::Undecryptable {
+                path: encrypted.clone(),
+                source,
+            })?;
+        let path = encrypted.with_extension("");
+        replace(&path, &plain)?;
+        fs::remove_file(&encrypted).map_err(|source| InputError::Unreadable {
+            path: encrypted,
+            source,
+        })?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let key = Key::new("correct horse battery staple\n");
+        let input = b"3   4\n4   3\n".repeat(20);
+        let sealed = key.encrypt(&input);
+        assert_ne!(
+            &sealed[MAGIC.len()..sealed.len() - TAG_BYTES],
+            input.as_slice()
+        );
+        assert_eq!(
+            sealed,
+            Key::new("correct horse battery staple").encrypt(&input)
+        );
+        assert_eq!(key.decrypt(&sealed).unwrap(), input);
+        assert_eq!(key.decrypt(&key.encrypt(b"")).unwrap(), b"");
+
+        assert!(matches!(
+            Key::new("another").decrypt(&sealed),
+            Err(CryptError::Mismatched)
+        ));
+        let mut altered = sealed.clone();
+        altered[MAGIC.len() + NONCE_BYTES] ^= 1;
+        assert!(matches!(key.decrypt(&altered), Err(CryptError::Mismatched)));
+        let mut renonced = sealed.clone();
+        renonced[MAGIC.len()] ^= 1;
+        assert!(matches!(
+            key.decrypt(&renonced),
+            Err(CryptError::Mismatched)
+        ));
+        assert!(matches!(key.decrypt(&input), Err(CryptError::Malformed)));
+        assert!(matches!(
+            key.decrypt(&sealed[..MAGIC.len() + NONCE_BYTES]),
+            Err(CryptError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn directories() {
+        let dir = parser::scratch_dir("crypt");
+        fs::create_dir_all(dir.join("2024")).unwrap();
+        fs::write(dir.join("2024/day1.txt"), "3   4\n").unwrap();
+        fs::write(dir.join("2024/day10.txt"), "0123\n").unwrap();
+        fs::write(dir.join("2024/answers.toml"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let key = Key::new("secret");
+        let encrypted = encrypt_dir(&dir, &key).unwrap();
+        assert_eq!(
+            encrypted,
+            [
+                dir.join("2024/day1.txt.enc"),
+                dir.join("2024/day10.txt.enc")
+            ]
+        );
+        assert!(!dir.join("2024/day1.txt").exists());
+        assert!(dir.join("2024/answers.toml").exists());
+        assert!(encrypt_dir(&dir, &key).unwrap().is_empty());
+
+        assert!(matches!(
+            decrypt_dir(&dir, &Key::new("wrong")),
+            Err(InputError::Undecryptable { .. })
+        ));
+        assert!(dir.join("2024/day1.txt.enc").exists());
+        assert_eq!(decrypt_dir(&dir, &key).unwrap().len(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("2024/day10.txt")).unwrap(),
+            "0123\n"
+        );
+        assert!(!dir.join("2024/day10.txt.enc").exists());
+    }
+}
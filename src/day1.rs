@@ -1,92 +1,107 @@
 use std::collections::{BinaryHeap, HashMap};
 
-use aoc_runner_derive::{aoc, aoc_generator};
-#[aoc_generator(day1)]
-fn parse(input: &str) -> (Vec<usize>, Vec<usize>) {
-    let mut list_1 = Vec::new();
-    let mut list_2 = Vec::new();
-
-    for line in input.trim().lines() {
-        let split = line.trim().split("   ").collect::<Vec<&str>>();
-        list_1.push(
-            split
-                .first()
-                .expect("Expected split to contain a first value")
-                .parse::<usize>()
-                .expect("Error parsing list 1 entry"),
-        );
-        list_2.push(
-            split
-                .last()
-                .expect("Expected split to contain a last value")
-                .parse::<usize>()
-                .expect("Error parsing list 2 entry"),
-        );
+use crate::{
+    parser::{ParseError, Separator, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: usize = 1;
+    type Input = (Vec<usize>, Vec<usize>);
+    /// Wide enough that no input small enough to read can overflow it, whatever its IDs.
+    type Answer = i128;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(1, input);
+        let mut list_1 = Vec::new();
+        let mut list_2 = Vec::new();
+
+        for line in source.lines() {
+            let [first, second] = source.array(line, Separator::Whitespace, "a location ID")?;
+            list_1.push(first);
+            list_2.push(second);
+        }
+
+        Ok((list_1, list_2))
     }
 
-    (list_1, list_2)
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        let mut list_1 = BinaryHeap::from(input.0.clone());
+        let mut list_2 = BinaryHeap::from(input.1.clone());
+        let mut res = 0;
+
+        while let Some(l1) = list_1.pop() {
+            let l2 = list_2
+                .pop()
+                .expect("Expected list 2 to have a value to pop");
+            res += l1.abs_diff(l2) as i128;
+        }
 
-#[aoc(day1, part1)]
-fn part1(input: &(Vec<usize>, Vec<usize>)) -> usize {
-    let mut list_1 = BinaryHeap::from(input.0.clone());
-    let mut list_2 = BinaryHeap::from(input.1.clone());
-    let mut res = 0;
-
-    while let Some(l1) = list_1.pop() {
-        let l2 = list_2
-            .pop()
-            .expect("Expected list 2 to have a value to pop");
-        let delta = (l1 as isize - l2 as isize).unsigned_abs();
-        res += delta;
+        res
     }
 
-    res
-}
+    fn part2(input: &Self::Input) -> Self::Answer {
+        let counts = input.1.iter().fold(HashMap::new(), |mut acc, num| {
+            *acc.entry(num).or_insert(0) += 1;
+            acc
+        });
 
-#[aoc(day1, part2)]
-fn part2(input: &(Vec<usize>, Vec<usize>)) -> usize {
-    let counts = input.1.iter().fold(HashMap::new(), |mut acc, num| {
-        *acc.entry(num).or_insert(0) += 1;
-        acc
-    });
+        input.0.iter().fold(0, |mut acc, num| {
+            if let Some(count) = counts.get(num) {
+                acc += *num as i128 * count;
+            }
+            acc
+        })
+    }
+}
 
-    input.0.iter().fold(0, |mut acc, num| {
-        if let Some(count) = counts.get(num) {
-            acc += num * count;
-        }
-        acc
-    })
+impl Extras for Day1 {
+    /// `size` pairs of five digit location IDs, where the right list often repeats the
+    /// left's.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let left = (0..size.max(1))
+            .map(|_| rng.range(10000..100000))
+            .collect::<Vec<_>>();
+        let lines = left
+            .iter()
+            .map(|id| {
+                let right = if rng.chance(0.3) {
+                    *rng.choose(&left)
+                } else {
+                    rng.range(10000..100000)
+                };
+                format!("{}   {}", id, right)
+            })
+            .collect::<Vec<_>>();
+        Some(lines.join("\n"))
+    }
 }
 
+crate::registry::register!(Day1, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST: &str = "
-        3   4
-        4   3
-        2   5
-        1   3
-        3   9
-        3   3
-    ";
 
-    #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 11);
-    }
+    crate::examples::test_day!(Day1);
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 31);
+    fn parse_error() {
+        let error = Day1::parse("3   4\n4 x").unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "x"));
+        assert!(Day1::parse("3   4\n4").is_err());
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(1));
-        assert_eq!(part1(input), 1530215);
-        assert_eq!(part2(input), 26800609);
+    fn large_ids() {
+        let max = u64::MAX as i128;
+        let input =
+            Day1::parse("18446744073709551615   18446744073709551615\n1   18446744073709551615")
+                .unwrap();
+        assert_eq!(Day1::part1(&input), max - 1);
+        assert_eq!(Day1::part2(&input), max * 2);
     }
 }
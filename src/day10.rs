@@ -1,194 +1,169 @@
-use std::collections::{HashMap, HashSet};
-
-type Point = (isize, isize);
-
-struct Facility {
-    map: HashMap<Point, usize>,
+use std::collections::HashSet;
+
+use crate::{
+    geometry::Point,
+    graph::{self, Control},
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::{self, Rng},
+    render::{self, Canvas, Colour, Glyph},
+    Extras, Solution,
+};
+
+pub struct Facility {
+    map: Grid<usize>,
     trailheads: Vec<Point>,
 }
 
 impl Facility {
-    fn next_steps(&self, point: &Point) -> Vec<Point> {
-        let height = self.map.get(point).expect("Expected point to exist in map");
-        vec![
-            (point.0 - 1, point.1),
-            (point.0, point.1 + 1),
-            (point.0 + 1, point.1),
-            (point.0, point.1 - 1),
-        ]
-        .into_iter()
-        .filter(|point| match self.map.get(point) {
+    fn next_steps(&self, point: &Point) -> impl Iterator<Item = Point> + '_ {
+        let height = self.map[*point];
+        self.map
+            .neighbours4(*point)
             // The question states that each step must increment by exactly 1 to be valid.
-            Some(value) => *value == (height + 1),
-            None => false,
-        })
-        .collect()
+            .filter(move |next| self.map[*next] == height + 1)
+    }
+
+    fn previous_steps(&self, point: &Point) -> impl Iterator<Item = Point> + '_ {
+        let height = self.map[*point];
+        self.map
+            .neighbours4(*point)
+            .filter(move |previous| self.map[*previous] + 1 == height)
+    }
+
+    /// Every point on a trail from a trailhead to a summit.
+    fn trails(&self) -> HashSet<Point> {
+        let from_trailheads = graph::bfs(
+            self.trailheads.iter().copied(),
+            |point| self.next_steps(point),
+            |_, _| Control::Continue,
+        );
+        let to_summits = graph::bfs(
+            self.map.find_all(&9),
+            |point| self.previous_steps(point),
+            |_, _| Control::Continue,
+        );
+        from_trailheads.intersection(&to_summits).copied().collect()
+    }
+
+    fn summit(&self, point: &Point) -> bool {
+        self.map[*point] == 9
     }
 
-    fn hike(&self, point: &Point) -> Vec<Point> {
-        let height = self.map.get(point).expect("Expected point to exist in map");
-        if *height == 9 {
-            Vec::from([*point])
-        } else {
-            self.next_steps(point)
-                .iter()
-                .flat_map(|next_point| self.hike(next_point))
-                .collect()
-        }
+    fn score(&self, trailhead: &Point) -> usize {
+        let mut summits = 0;
+        graph::dfs(
+            *trailhead,
+            |point| self.next_steps(point),
+            |point, _| {
+                if self.summit(point) {
+                    summits += 1;
+                }
+                Control::Continue
+            },
+        );
+        summits
+    }
+
+    fn rating(&self, trailhead: &Point) -> usize {
+        graph::count_paths(
+            *trailhead,
+            |point| self.next_steps(point),
+            |point| self.summit(point),
+        )
+        .expect("Heights should only increase along a trail")
     }
 
     fn set_off(&self) -> (usize, usize) {
-        self.trailheads.iter().map(|point| self.hike(point)).fold(
-            (0, 0),
-            |(acc_score, acc_rating), summits| {
+        self.trailheads
+            .iter()
+            .fold((0, 0), |(acc_score, acc_rating), trailhead| {
                 // Part 1 is how many unique SUMMITS a trailhead can reach.
                 // Part 2 is how many unique TRAILS from a trailhead lead to a summit.
-                let summits_set: HashSet<_> = summits.iter().collect();
-                (acc_score + summits_set.len(), acc_rating + summits.len())
-            },
-        )
+                (
+                    acc_score + self.score(trailhead),
+                    acc_rating + self.rating(trailhead),
+                )
+            })
     }
 }
 
-#[aoc_generator(day10)]
-fn parse(input: &str) -> Facility {
-    let mut map = HashMap::new();
-    let mut trailheads = Vec::new();
-
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, c) in line.trim().chars().enumerate() {
-            let n = if c == '.' {
-                // Set to an arbitrary number higher than the summit.
-                10
-            } else {
-                c.to_digit(10).expect("Failed to convert char")
-            } as usize;
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+    type Input = Facility;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let (map, markers) = Grid::parse_marked(
+            &Source::new(10, input),
+            |c| c == '0',
+            |c| {
+                if c == '.' {
+                    // Set to an arbitrary number higher than the summit.
+                    Some(10)
+                } else {
+                    c.to_digit(10).map(|n| n as usize)
+                }
+            },
+        )?;
+        let trailheads = markers.all('0');
 
-            if n == 0 {
-                trailheads.push((row as isize, col as isize));
-            }
+        Ok(Facility { map, trailheads })
+    }
 
-            map.insert((row as isize, col as isize), n);
-        }
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.set_off().0
     }
 
-    Facility { map, trailheads }
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input.set_off().1
+    }
 }
 
-#[aoc(day10, part1)]
-fn part1(input: &Facility) -> usize {
-    input.set_off().0
-}
+impl Extras for Day10 {
+    /// A square map of about `size` positions, mostly rising by one with each step right or
+    /// down, so that trails branch and rejoin everywhere.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        Some(random::grid(size, |row, col| {
+            let height = if rng.chance(0.8) {
+                (row + col) % 10
+            } else {
+                rng.below(10)
+            };
+            char::from_digit(height as u32, 10).expect("Height should be one digit")
+        }))
+    }
 
-#[aoc(day10, part2)]
-fn part2(input: &Facility) -> usize {
-    input.set_off().1
+    /// The heights along every trail, shaded from the trailheads to the summits.
+    fn render(input: &Self::Input) -> Option<Canvas> {
+        let trails = input.trails();
+        Some(render::draw(
+            &input.map,
+            |point, height| match char::from_digit(*height as u32, 10) {
+                Some(digit) if trails.contains(&point) => {
+                    let shade = (*height * 25) as u8;
+                    Glyph::new(digit, Colour::new(30 + shade, 255 - shade, 60))
+                }
+                _ => Glyph::new('.', Colour::GREY),
+            },
+        ))
+    }
 }
 
+crate::registry::register!(Day10, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-    use test_case::test_case;
-
-    const INPUT_0: &str = "
-        0123
-        1234
-        8765
-        9876
-    ";
-
-    const INPUT_1: &str = "
-        ...0...
-        ...1...
-        ...2...
-        6543456
-        7.....7
-        8.....8
-        9.....9
-    ";
-
-    const INPUT_2: &str = "
-        ..90..9
-        ...1.98
-        ...2..7
-        6543456
-        765.987
-        876....
-        987....
-    ";
-
-    const INPUT_3: &str = "
-        10..9..
-        2...8..
-        3...7..
-        4567654
-        ...8..3
-        ...9..2
-        .....01
-    ";
-
-    const INPUT_4: &str = "
-        89010123
-        78121874
-        87430965
-        96549874
-        45678903
-        32019012
-        01329801
-        10456732
-    ";
-
-    const INPUT_5: &str = "
-        .....0.
-        ..4321.
-        ..5..2.
-        ..6543.
-        ..7..4.
-        ..8765.
-        ..9....
-    ";
-
-    const INPUT_6: &str = "
-        ..90..9
-        ...1.98
-        ...2..7
-        6543456
-        765.987
-        876....
-        987....
-    ";
-
-    const INPUT_7: &str = "
-        012345
-        123456
-        234567
-        345678
-        4.6789
-        56789.
-    ";
-
-    #[test_case(INPUT_0, 1; "input_0")]
-    #[test_case(INPUT_1, 2; "input_1")]
-    #[test_case(INPUT_2, 4; "input_2")]
-    #[test_case(INPUT_3, 3; "input_3")]
-    #[test_case(INPUT_4, 36; "input_4")]
-    fn part1_example(input: &str, want: usize) {
-        assert_eq!(part1(&parse(input)), want);
-    }
 
-    #[test_case(INPUT_5, 3; "input_5")]
-    #[test_case(INPUT_6, 13; "input_6")]
-    #[test_case(INPUT_7, 227; "input_7")]
-    #[test_case(INPUT_4, 81; "input_4")]
-    fn part2_example(input: &str, want: usize) {
-        assert_eq!(part2(&parse(input)), want);
-    }
+    crate::examples::test_day!(Day10);
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(10));
-        assert_eq!(part1(input), 841);
-        assert_eq!(part2(input), 1875);
+    fn render_trails() {
+        let input = Day10::parse("0123\n9.54\n8769").unwrap();
+        let canvas = Day10::render(&input).unwrap();
+        assert_eq!(render::plain(&canvas), "0123\n9.54\n876.\n");
     }
 }
@@ -2,11 +2,22 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
+use crate::{
+    math::Digits,
+    parser::{ParseError, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
 lazy_static! {
     static ref STORE: Mutex<PebbleStore> = Mutex::new(PebbleStore::default());
 }
 
-type Pebbles = Vec<usize>;
+pub type Pebbles = Vec<usize>;
+
+/// The most digits a pebble can start with. An odd number of digits grows by at most seven
+/// before splitting, so from here no pebble ever outgrows a `u64`.
+const MAX_DIGITS: u32 = 12;
 
 #[derive(Default)]
 struct PebbleStore {
@@ -33,17 +44,14 @@ impl Blinkable for usize {
             return pebbles.to_vec();
         }
 
-        let pebble_string = self.to_string();
+        let digits = self.digits();
 
         let pebbles = if self == 0 {
             vec![1]
-        } else if pebble_string.len() % 2 == 0 {
+        } else if digits.is_multiple_of(2) {
             // Even number of digits
-            let (left, right) = pebble_string.split_at(pebble_string.len() / 2);
-            vec![
-                left.parse().expect("Error parsing left pebble"),
-                right.parse().expect("Error parsing right pebble"),
-            ]
+            let (left, right) = self.split_digits(digits / 2);
+            vec![left, right]
         } else {
             // Odd number of digits
             vec![self * 2024]
@@ -68,42 +76,68 @@ fn blink_n(input: &Pebbles, n: usize) -> usize {
     pebbles.len()
 }
 
-#[aoc_generator(day11)]
-fn parse(input: &str) -> Pebbles {
-    input
-        .split_whitespace()
-        .map(|pebble| pebble.parse().expect("Error parsing numbers"))
-        .collect()
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: usize = 11;
+    type Input = Pebbles;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(11, input);
+        input
+            .split_whitespace()
+            .map(|text| {
+                let pebble: usize = source.parse(text, "a pebble number")?;
+                if pebble.digits() > MAX_DIGITS {
+                    return Err(source.error(
+                        text,
+                        format!("expected a pebble number of at most {} digits", MAX_DIGITS),
+                    ));
+                }
+                Ok(pebble)
+            })
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer {
+        blink_n(input, 25)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer {
+        blink_counted(input, 75)
+    }
 }
 
-#[aoc(day11, part1)]
-fn part1(input: &Pebbles) -> usize {
-    blink_n(input, 25)
+impl Extras for Day11 {
+    /// `size` pebbles engraved with numbers of up to six digits.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let pebbles = (0..size.max(1))
+            .map(|_| rng.range(0..1_000_000).to_string())
+            .collect::<Vec<_>>();
+        Some(pebbles.join(" "))
+    }
 }
 
-#[aoc(day11, part2)]
-fn part2(input: &Pebbles) -> usize {
+/// Count the pebbles after `n` blinks, ignoring their order and grouping equal pebbles.
+fn blink_counted(input: &Pebbles, n: usize) -> usize {
     let mut pebbles: HashMap<usize, usize> = HashMap::new();
     input
         .iter()
         .for_each(|&num| *pebbles.entry(num).or_insert(0) += 1);
 
-    for _ in 0..75 {
+    for _ in 0..n {
         let mut split_pebbles = HashMap::new();
 
         for (&pebble, &count) in pebbles.iter() {
-            let pebble_string = pebble.to_string();
+            let digits = pebble.digits();
             if pebble == 0 {
                 *split_pebbles.entry(1).or_insert(0) += count;
-            } else if pebble_string.len() % 2 == 0 {
+            } else if digits.is_multiple_of(2) {
                 // Even number of digits
-                let (left, right) = pebble_string.split_at(pebble_string.len() / 2);
-                *split_pebbles
-                    .entry(left.parse().expect("Error parsing left pebble"))
-                    .or_insert(0) += count;
-                *split_pebbles
-                    .entry(right.parse().expect("Error parsing left pebble"))
-                    .or_insert(0) += count;
+                let (left, right) = pebble.split_digits(digits / 2);
+                *split_pebbles.entry(left).or_insert(0) += count;
+                *split_pebbles.entry(right).or_insert(0) += count;
             } else {
                 // Odd number of digits
                 *split_pebbles.entry(pebble * 2024).or_insert(0) += count;
@@ -115,24 +149,31 @@ fn part2(input: &Pebbles) -> usize {
     pebbles.values().sum()
 }
 
+impl Day11 {
+    /// The count map approach from `part2`, applied to part 1's 25 blinks.
+    pub fn part1_counted(input: &Pebbles) -> usize {
+        blink_counted(input, 25)
+    }
+}
+
+crate::registry::register!(Day11, part1 | part1_counted, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
+    use crate::examples;
     use test_case::test_case;
 
-    const INPUT_1: &str = "
-        0 1 10 99 999
-    ";
-
-    const INPUT_2: &str = "
-        125 17
-    ";
+    crate::examples::test_day!(Day11);
 
-    #[test_case(INPUT_1, 1, "1 2024 1 0 9 9 2021976"; "input_1")]
-    #[test_case(INPUT_2, 6, "2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2"; "input_2")]
-    fn split_example(input: &str, blinks: usize, want: &str) {
-        let mut pebbles = parse(input);
+    #[test_case("blink", 1, "1 2024 1 0 9 9 2021976")]
+    #[test_case(
+        "example",
+        6,
+        "2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2"
+    )]
+    fn split_example(name: &str, blinks: usize, want: &str) {
+        let mut pebbles = Day11::parse(&examples::input(11, name)).unwrap();
         for _ in 0..blinks {
             pebbles = pebbles.blink();
         }
@@ -143,15 +184,16 @@ mod tests {
         assert_eq!(result, want);
     }
 
-    #[test_case(INPUT_2, 55312; "input_2")]
-    fn part1_example(input: &str, want: usize) {
-        assert_eq!(part1(&parse(input)), want);
-    }
-
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(11));
-        assert_eq!(part1(input), 228668);
-        assert_eq!(part2(input), 270673834779359);
+    fn largest_pebbles() {
+        // This grows to 18 digits before it splits, as large as any pebble can get.
+        assert_eq!(Day11::part1(&Day11::parse("99999999999").unwrap()), 14862);
+
+        let error = Day11::parse("1 9999999999999").err().unwrap();
+        assert_eq!((error.line, error.column), (1, 3));
+        assert_eq!(
+            error.reason,
+            "expected a pebble number of at most 12 digits"
+        );
     }
 }
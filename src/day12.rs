@@ -1,62 +1,56 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
-type Point = (isize, isize);
-
-struct Garden {
-    map: HashMap<Point, char>,
+use std::collections::HashSet;
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    geometry::{Direction4, Point},
+    graph::{self, Control},
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::{self, Rng},
+    render::{self, Canvas, Colour, Glyph},
+    Extras, Solution,
+};
+
+pub struct Garden {
+    map: Grid<char>,
 }
 
 impl Garden {
     fn get_regions(&self) -> Vec<Region> {
         let mut regions = Vec::new();
         let mut visited = HashSet::new();
-        let mut min_bound = (isize::MAX, isize::MAX);
-        let mut max_bound = (isize::MIN, isize::MIN);
 
-        for (point, plant) in &self.map {
-            if visited.len() == self.map.len() {
+        for (point, plant) in self.map.iter() {
+            if visited.len() == self.map.width() * self.map.height() {
                 break;
             }
 
-            if visited.contains(point) {
+            if visited.contains(&point) {
                 continue;
             }
 
+            let mut min_bound = point;
+            let mut max_bound = point;
+
             // Collect plots in region
-            let mut plots = HashSet::new();
-
-            // Breadth first search
-            let mut queue = VecDeque::new();
-            visited.insert(point.to_owned());
-            queue.push_back(point.to_owned());
-
-            while let Some(plot) = queue.pop_front() {
-                plots.insert(plot);
-
-                // Determine the bounding box of the region
-                min_bound.0 = min_bound.0.min(plot.0);
-                min_bound.1 = min_bound.1.min(plot.1);
-                max_bound.0 = max_bound.0.max(plot.0);
-                max_bound.1 = max_bound.1.max(plot.1);
-
-                vec![
-                    (plot.0 - 1, plot.1),
-                    (plot.0, plot.1 + 1),
-                    (plot.0 + 1, plot.1),
-                    (plot.0, plot.1 - 1),
-                ]
-                .into_iter()
-                .filter(|p| match self.map.get(p) {
-                    Some(c) => c == plant,
-                    None => false,
-                })
-                .for_each(|f| {
-                    if !visited.contains(&f) {
-                        visited.insert(f);
-                        queue.push_back(f);
-                    }
-                });
-            }
+            let plots = graph::bfs(
+                [point],
+                |plot| {
+                    self.map
+                        .neighbours4(*plot)
+                        .filter(|p| self.map[*p] == *plant)
+                },
+                |plot, _| {
+                    // Determine the bounding box of the region
+                    min_bound.row = min_bound.row.min(plot.row);
+                    min_bound.col = min_bound.col.min(plot.col);
+                    max_bound.row = max_bound.row.max(plot.row);
+                    max_bound.col = max_bound.col.max(plot.col);
+                    Control::Continue
+                },
+            );
+            visited.extend(plots.iter().copied());
 
             regions.push(Region {
                 points: plots,
@@ -80,15 +74,10 @@ impl Region {
         self.points
             .iter()
             .flat_map(|point| {
-                vec![
-                    (point.0 - 1, point.1),
-                    (point.0, point.1 + 1),
-                    (point.0 + 1, point.1),
-                    (point.0, point.1 - 1),
-                ]
-                .into_iter()
-                .filter(|p| !self.points.contains(p))
-                .map(|_| 1)
+                Direction4::iter()
+                    .map(|direction| point.step(direction))
+                    .filter(|p| !self.points.contains(p))
+                    .map(|_| 1)
             })
             .sum()
     }
@@ -97,13 +86,13 @@ impl Region {
         let mut sides = 0;
 
         // Horizontal scan
-        for row in self.min_bound.0..=self.max_bound.0 {
+        for row in self.min_bound.row..=self.max_bound.row {
             let mut t_edge = None;
             let mut b_edge = None;
 
-            for col in self.min_bound.1..=self.max_bound.1 {
-                if let Some(point) = self.points.get(&(row, col)) {
-                    t_edge = match self.points.get(&(row - 1, col)) {
+            for col in self.min_bound.col..=self.max_bound.col {
+                if let Some(point) = self.points.get(&Point::new(row, col)) {
+                    t_edge = match self.points.get(&point.step(Direction4::North)) {
                         Some(_) => None,
                         None => {
                             // It's a top edge
@@ -113,7 +102,7 @@ impl Region {
                             Some(point)
                         }
                     };
-                    b_edge = match self.points.get(&(row + 1, col)) {
+                    b_edge = match self.points.get(&point.step(Direction4::South)) {
                         Some(_) => None,
                         None => {
                             // It's a bottom edge
@@ -131,13 +120,13 @@ impl Region {
         }
 
         // Vertical scan
-        for col in self.min_bound.1..=self.max_bound.1 {
+        for col in self.min_bound.col..=self.max_bound.col {
             let mut l_edge = None;
             let mut r_edge = None;
 
-            for row in self.min_bound.0..=self.max_bound.0 {
-                if let Some(point) = self.points.get(&(row, col)) {
-                    l_edge = match self.points.get(&(row, col - 1)) {
+            for row in self.min_bound.row..=self.max_bound.row {
+                if let Some(point) = self.points.get(&Point::new(row, col)) {
+                    l_edge = match self.points.get(&point.step(Direction4::West)) {
                         Some(_) => None,
                         None => {
                             // It's a left edge
@@ -147,7 +136,7 @@ impl Region {
                             Some(point)
                         }
                     };
-                    r_edge = match self.points.get(&(row, col + 1)) {
+                    r_edge = match self.points.get(&point.step(Direction4::East)) {
                         Some(_) => None,
                         None => {
                             // It's a right edge
@@ -180,104 +169,150 @@ impl Region {
     }
 }
 
-#[aoc_generator(day12)]
-fn parse(input: &str) -> Garden {
-    let mut map = HashMap::new();
+pub struct Day12;
 
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, c) in line.trim().chars().enumerate() {
-            map.insert((row as isize, col as isize), c);
-        }
+impl Solution for Day12 {
+    const DAY: usize = 12;
+    type Input = Garden;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(Garden {
+            map: Grid::parse(&Source::new(12, input), Some)?,
+        })
     }
 
-    Garden { map }
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.get_regions().iter().map(|region| region.cost()).sum()
+    }
 
-#[aoc(day12, part1)]
-fn part1(input: &Garden) -> usize {
-    input.get_regions().iter().map(|region| region.cost()).sum()
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input
+            .get_regions()
+            .iter()
+            .map(|region| region.discount_cost())
+            .sum()
+    }
 }
 
-#[aoc(day12, part2)]
-fn part2(input: &Garden) -> usize {
-    input
-        .get_regions()
-        .iter()
-        .map(|region| region.discount_cost())
-        .sum()
+impl Extras for Day12 {
+    /// A square garden of about `size` plots of up to five plants, which tend to grow in
+    /// clumps.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let side = random::side(size);
+        let plants = &['A', 'B', 'C', 'D', 'E'][..rng.range(1..6)];
+        let mut plots: Vec<char> = Vec::with_capacity(side * side);
+        Some(random::grid(size, |row, col| {
+            let plant = if col > 0 && rng.chance(0.4) {
+                plots[plots.len() - 1]
+            } else if row > 0 && rng.chance(0.4) {
+                plots[plots.len() - side]
+            } else {
+                *rng.choose(plants)
+            };
+            plots.push(plant);
+            plant
+        }))
+    }
+
+    /// The garden's plants, coloured by region.
+    fn render(input: &Self::Input) -> Option<Canvas> {
+        let mut region_of = input.map.map(|_| 0);
+        for (index, region) in input.get_regions().iter().enumerate() {
+            for point in &region.points {
+                region_of[*point] = index;
+            }
+        }
+        Some(render::draw(&input.map, |point, plant| {
+            Glyph::new(*plant, Colour::palette(region_of[point]))
+        }))
+    }
 }
 
+crate::registry::register!(Day12, part1, part2);
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
-    use crate::parser;
-    use test_case::test_case;
-
-    const INPUT_1: &str = "
-        AAAA
-        BBCD
-        BBCC
-        EEEC
-    ";
-
-    const INPUT_2: &str = "
-        OOOOO
-        OXOXO
-        OOOOO
-        OXOXO
-        OOOOO
-    ";
-
-    const INPUT_3: &str = "
-        RRRRIICCFF
-        RRRRIICCCF
-        VVRRRCCFFF
-        VVRCCCJFFF
-        VVVVCJJCFE
-        VVIVCCJJEE
-        VVIIICJJEE
-        MIIIIIJJEE
-        MIIISIJEEE
-        MMMISSJEEE
-    ";
-
-    const INPUT_4: &str = "
-        EEEEE
-        EXXXX
-        EEEEE
-        EXXXX
-        EEEEE
-    ";
-
-    const INPUT_5: &str = "
-        AAAAAA
-        AAABBA
-        AAABBA
-        ABBAAA
-        ABBAAA
-        AAAAAA
-    ";
-
-    #[test_case(INPUT_1, 140)]
-    #[test_case(INPUT_2, 772)]
-    #[test_case(INPUT_3, 1930)]
-    fn part1_example(input: &str, want: usize) {
-        assert_eq!(part1(&parse(input)), want);
+    use crate::{examples, random};
+
+    crate::examples::test_day!(Day12);
+
+    #[test]
+    fn render_regions() {
+        let canvas = Day12::render(&Day12::parse(&examples::input(12, "nested")).unwrap()).unwrap();
+        assert_eq!(
+            render::plain(&canvas),
+            "OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO\n"
+        );
+        assert_eq!(
+            canvas[Point::new(0, 0)].colour,
+            canvas[Point::new(4, 4)].colour
+        );
+        assert_ne!(
+            canvas[Point::new(1, 1)].colour,
+            canvas[Point::new(1, 3)].colour
+        );
+    }
+
+    /// The fence prices, without and with the bulk discount, of a garden whose regions are
+    /// labelled by repeatedly giving neighbouring plots of the same plant the lower of
+    /// their labels until nothing changes.
+    fn prices_oracle(garden: &Garden) -> (usize, usize) {
+        let map = &garden.map;
+        let mut labels = Grid::new(
+            map.width(),
+            map.height(),
+            (0..map.width() * map.height()).collect(),
+        );
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for point in map.points() {
+                for neighbour in map.neighbours4(point) {
+                    if map[neighbour] == map[point] && labels[neighbour] < labels[point] {
+                        labels[point] = labels[neighbour];
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let fenced = |point: Point, direction: Direction4| {
+            labels.get(point.step(direction)) != Some(&labels[point])
+        };
+        let mut regions = HashMap::<usize, (usize, usize, usize)>::new();
+        for point in map.points() {
+            let (area, perimeter, sides) = regions.entry(labels[point]).or_default();
+            *area += 1;
+            for direction in Direction4::iter() {
+                if fenced(point, direction) {
+                    *perimeter += 1;
+                    // Count each side at the plot at one end of it.
+                    let along = point.step(direction.turn_left());
+                    if labels.get(along) != Some(&labels[point]) || !fenced(along, direction) {
+                        *sides += 1;
+                    }
+                }
+            }
+        }
+
+        regions
+            .values()
+            .fold((0, 0), |(price, discount), (area, perimeter, sides)| {
+                (price + area * perimeter, discount + area * sides)
+            })
     }
 
-    #[test_case(INPUT_1, 80)]
-    #[test_case(INPUT_2, 436)]
-    #[test_case(INPUT_3, 1206)]
-    #[test_case(INPUT_4, 236)]
-    #[test_case(INPUT_5, 368)]
-    fn part2_example(input: &str, want: usize) {
-        assert_eq!(part2(&parse(input)), want);
+    #[test]
+    fn part1_oracle() {
+        random::check_oracle::<Day12>(Day12::part1, |input| prices_oracle(input).0, 2000, 144);
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(12));
-        assert_eq!(part1(input), 1477762);
-        assert_eq!(part2(input), 923480);
+    fn part2_oracle() {
+        random::check_oracle::<Day12>(Day12::part2, |input| prices_oracle(input).1, 2000, 144);
     }
 }
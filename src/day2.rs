@@ -1,28 +1,79 @@
-use aoc_runner_derive::{aoc, aoc_generator};
-#[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<Vec<isize>> {
-    input
-        .trim()
-        .lines()
-        .map(|line| {
-            line.trim()
-                .split(' ')
-                .map(|n| n.parse().expect("Error parsing level"))
-                .collect()
-        })
-        .collect()
+use crate::{
+    parser::{ParseError, Separator, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: usize = 2;
+    type Input = Vec<Vec<isize>>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(2, input);
+        source
+            .lines()
+            .map(|line| source.list(line, Separator::Whitespace, "a level"))
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.iter().filter(|report| report.safe(1, 3)).count()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input.iter().filter(|report| report.tolerable(1, 3)).count()
+    }
+}
+
+impl Extras for Day2 {
+    /// `size` reports of one to eight levels, most of which step steadily enough to be safe,
+    /// or nearly.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let reports = (0..size.max(1))
+            .map(|_| {
+                let direction = if rng.chance(0.5) { 1 } else { -1 };
+                let mut level = rng.range(10..90) as isize;
+                let mut levels = vec![level.to_string()];
+                for _ in 1..rng.range(1..9) {
+                    level += if rng.chance(0.85) {
+                        direction * rng.range(1..4) as isize
+                    } else {
+                        rng.range(0..11) as isize - 5
+                    };
+                    levels.push(level.to_string());
+                }
+                levels.join(" ")
+            })
+            .collect::<Vec<_>>();
+        Some(reports.join("\n"))
+    }
 }
 
 trait Report {
     fn safe(&self, min_diff: isize, max_diff: isize) -> bool;
+
+    /// Whether the report is safe, or would be with any one level removed.
+    fn tolerable(&self, min_diff: isize, max_diff: isize) -> bool;
 }
 
 impl Report for Vec<isize> {
     fn safe(&self, min_diff: isize, max_diff: isize) -> bool {
-        let mut prev_diff = self[1] - self[0];
+        // With fewer than two levels there's no step to be unsafe.
+        let Some(first) = self.windows(2).next() else {
+            return true;
+        };
+        // A step too big to even subtract is far too big to be safe.
+        let Some(mut prev_diff) = first[1].checked_sub(first[0]) else {
+            return false;
+        };
 
         for w in self.windows(2) {
-            let diff = w[1] - w[0];
+            let Some(diff) = w[1].checked_sub(w[0]) else {
+                return false;
+            };
             if diff.abs() < min_diff || diff.abs() > max_diff || diff.signum() != prev_diff.signum()
             {
                 return false;
@@ -31,63 +82,144 @@ impl Report for Vec<isize> {
         }
         true
     }
-}
 
-#[aoc(day2, part1)]
-fn part1(input: &[Vec<isize>]) -> usize {
-    input.iter().filter(|report| report.safe(1, 3)).count()
+    fn tolerable(&self, min_diff: isize, max_diff: isize) -> bool {
+        self.safe(min_diff, max_diff)
+            || (0..self.len()).any(|i| {
+                let mut report = self.clone();
+                report.remove(i);
+                report.safe(min_diff, max_diff)
+            })
+    }
 }
 
-#[aoc(day2, part2)]
-fn part2(input: &Vec<Vec<isize>>) -> usize {
-    let mut count = 0;
-
-    for report in input {
-        if report.safe(1, 3) {
-            count += 1;
-        } else {
-            for i in 0..report.len() {
-                let mut r = report.clone();
-                r.remove(i);
-                if r.safe(1, 3) {
-                    count += 1;
-                    break;
-                }
-            }
+/// The index of the level at which the report, ignoring the level at `skip`, becomes
+/// unsafe.
+fn first_unsafe(
+    report: &[isize],
+    skip: Option<usize>,
+    min_diff: isize,
+    max_diff: isize,
+) -> Option<usize> {
+    let mut levels = report.iter().enumerate().filter(|(i, _)| Some(*i) != skip);
+    let (_, mut prev) = levels.next()?;
+    let mut prev_diff: isize = 0;
+
+    for (i, level) in levels {
+        let Some(diff) = level.checked_sub(*prev) else {
+            return Some(i);
+        };
+        if diff.abs() < min_diff
+            || diff.abs() > max_diff
+            || (prev_diff != 0 && diff.signum() != prev_diff.signum())
+        {
+            return Some(i);
         }
+        prev_diff = diff;
+        prev = level;
+    }
+    None
+}
+
+impl Day2 {
+    /// An O(n) alternative to `part2` which doesn't clone each report for every removal.
+    pub fn part2_linear(input: &[Vec<isize>]) -> usize {
+        input
+            .iter()
+            .filter(|report| match first_unsafe(report, None, 1, 3) {
+                None => true,
+                // Only removing one of the two levels either side of the failure, or the one
+                // before them which may have set the wrong direction, can make it safe.
+                Some(i) => (i.saturating_sub(2)..=i)
+                    .any(|skip| first_unsafe(report, Some(skip), 1, 3).is_none()),
+            })
+            .count()
     }
 
-    count
+    /// `part2`, checking reports in parallel.
+    #[cfg(feature = "parallel")]
+    pub fn part2_parallel(input: &[Vec<isize>]) -> usize {
+        use rayon::prelude::*;
+
+        input
+            .par_iter()
+            .filter(|report| report.tolerable(1, 3))
+            .count()
+    }
 }
 
+#[cfg(not(feature = "parallel"))]
+crate::registry::register!(Day2, part1, part2 | part2_linear);
+#[cfg(feature = "parallel")]
+crate::registry::register!(Day2, part1, part2_parallel | part2 | part2_linear);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
+    use crate::random;
+    use test_case::test_case;
+
+    crate::examples::test_day!(Day2);
 
-    const TEST: &str = "
-        7 6 4 2 1
-        1 2 7 8 9
-        9 7 6 2 1
-        1 3 2 4 5
-        8 6 4 4 1
-        1 3 6 7 9
-    ";
+    #[test]
+    fn extreme_levels() {
+        let input = Day2::parse("9223372036854775807 -9223372036854775808").unwrap();
+        assert_eq!(Day2::part1(&input), 0);
+        assert_eq!(Day2::part2(&input), 1);
+        assert_eq!(Day2::part2_linear(&input), 1);
+    }
 
     #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 2);
+    fn short_reports() {
+        assert_eq!(Day2::part1(&Day2::parse("5\n1 2\n5 9").unwrap()), 2);
+        assert_eq!(Day2::part2(&Day2::parse("5\n1 2\n5 9").unwrap()), 3);
+    }
+
+    #[test_case("5 1 2 3 4"; "first_removed")]
+    #[test_case("1 2 3 4 9"; "last_removed")]
+    #[test_case("1 2 9 3 4"; "middle_removed")]
+    #[test_case("3 2 4 5 6"; "direction_set_by_first")]
+    #[test_case("1 2 3 2 1"; "unrecoverable")]
+    #[test_case("1 1 1 2 3"; "repeated")]
+    #[test_case("5"; "single_level")]
+    #[test_case("5 9"; "two_levels")]
+    fn part2_implementations_agree(report: &str) {
+        let input = Day2::parse(report).unwrap();
+        assert_eq!(Day2::part2_linear(&input), Day2::part2(&input));
+    }
+
+    /// Whether every step is by one to three levels, all in the same direction.
+    fn safe_oracle(report: &[isize]) -> bool {
+        let steps = report.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        steps.iter().all(|step| (1..=3).contains(step))
+            || steps.iter().all(|step| (-3..=-1).contains(step))
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 4);
+    fn part1_oracle() {
+        random::check_oracle::<Day2>(
+            Day2::part1,
+            |input| input.iter().filter(|report| safe_oracle(report)).count(),
+            2000,
+            50,
+        );
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(2));
-        assert_eq!(part1(input), 314);
-        assert_eq!(part2(input), 373);
+    fn part2_oracle() {
+        let oracle = |input: &Vec<Vec<isize>>| {
+            input
+                .iter()
+                .filter(|report| {
+                    (0..report.len()).any(|skip| {
+                        let mut report = report.to_vec();
+                        report.remove(skip);
+                        safe_oracle(&report)
+                    })
+                })
+                .count()
+        };
+        random::check_oracle::<Day2>(Day2::part2, oracle, 2000, 50);
+        random::check_oracle::<Day2>(|input| Day2::part2_linear(input), oracle, 2000, 50);
     }
 }
@@ -1,88 +1,108 @@
-use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
 
-enum Operation {
+use crate::{
+    parser::{ParseError, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
+pub enum Operation {
     Disable,
     Enable,
     Mul(usize, usize),
 }
 
-#[aoc_generator(day3)]
-fn parse(input: &str) -> Vec<Operation> {
-    Regex::new(r"mul\((\d+),(\d+)\)|(do)(\(\))|(don't)(\(\))")
-        .expect("Unable to initialise regex")
-        .captures_iter(input)
-        .map(|c| c.extract())
-        .map(|(entry, [first, second])| match entry {
-            "do()" => Operation::Enable,
-            "don't()" => Operation::Disable,
-            _ => Operation::Mul(
-                first.parse().expect("Unable to parse first number"),
-                second.parse().expect("Unable to parse second number"),
-            ),
-        })
-        .collect()
-}
+pub struct Day3;
 
-#[aoc(day3, part1)]
-fn part1(input: &[Operation]) -> usize {
-    input
-        .iter()
-        .map(|operation| match operation {
-            Operation::Mul(x, y) => x * y,
-            _ => 0,
-        })
-        .sum()
-}
+impl Solution for Day3 {
+    const DAY: usize = 3;
+    type Input = Vec<Operation>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(3, input);
+        // Only numbers of one to three digits make an instruction, as the puzzle says.
+        Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|(do)(\(\))|(don't)(\(\))")
+            .expect("Unable to initialise regex")
+            .captures_iter(input)
+            .map(|c| c.extract())
+            .map(|(entry, [first, second])| match entry {
+                "do()" => Ok(Operation::Enable),
+                "don't()" => Ok(Operation::Disable),
+                _ => Ok(Operation::Mul(
+                    source.parse(first, "a number")?,
+                    source.parse(second, "a number")?,
+                )),
+            })
+            .collect()
+    }
 
-#[aoc(day3, part2)]
-fn part2(input: &[Operation]) -> usize {
-    let mut enabled = true;
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input
+            .iter()
+            .map(|operation| match operation {
+                Operation::Mul(x, y) => x * y,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer {
+        let mut enabled = true;
 
-    input
-        .iter()
-        .map(|op| match op {
-            Operation::Disable => {
-                enabled = false;
-                0
-            }
-            Operation::Enable => {
-                enabled = true;
-                0
-            }
-            Operation::Mul(x, y) => {
-                if enabled {
-                    return x * y;
+        input
+            .iter()
+            .map(|op| match op {
+                Operation::Disable => {
+                    enabled = false;
+                    0
+                }
+                Operation::Enable => {
+                    enabled = true;
+                    0
                 }
-                0
-            }
-        })
-        .sum()
+                Operation::Mul(x, y) => {
+                    if enabled {
+                        return x * y;
+                    }
+                    0
+                }
+            })
+            .sum()
+    }
 }
 
+impl Extras for Day3 {
+    /// `size` fragments of corrupted memory, mostly instructions but some only nearly.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        Some(
+            (0..size.max(1))
+                .map(|_| match rng.below(8) {
+                    0 => "do()".to_string(),
+                    1 => "don't()".to_string(),
+                    2 => format!("mul({},{}]", rng.range(1..1000), rng.range(1..1000)),
+                    3 => format!("mul ( {},{} )", rng.range(1..1000), rng.range(1..1000)),
+                    4 => rng
+                        .choose(&["select()", "how()", "@", "from(", "$what", "^'"])
+                        .to_string(),
+                    _ => format!("mul({},{})", rng.range(1..1000), rng.range(1..1000)),
+                })
+                .collect(),
+        )
+    }
+}
+
+crate::registry::register!(Day3, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const PART_1: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
-    const PART_2: &str =
-        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
 
-    #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(PART_1)), 161);
-    }
-
-    #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(PART_2)), 48);
-    }
+    crate::examples::test_day!(Day3);
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(3));
-        assert_eq!(part1(input), 188116424);
-        assert_eq!(part2(input), 104245808);
+    fn long_numbers() {
+        let input = Day3::parse("mul(9999999999,9999999999)mul(1234,5)mul(2,3)").unwrap();
+        assert_eq!(Day3::part1(&input), 6);
     }
 }
@@ -1,42 +1,17 @@
-use std::collections::{HashMap, HashSet};
-
-type Point = (isize, isize);
-
-#[derive(Eq, PartialEq, Hash)]
-enum Direction {
-    North,
-    NorthEast,
-    East,
-    SouthEast,
-    South,
-    SouthWest,
-    West,
-    NorthWest,
-}
+use std::collections::HashSet;
 
-trait Offsetable {
-    fn offset(&self, offset: isize, direction: &Direction) -> Point;
-}
+use strum::IntoEnumIterator;
 
-impl Offsetable for Point {
-    fn offset(&self, offset: isize, direction: &Direction) -> Point {
-        match direction {
-            Direction::North => (self.0 - offset, self.1),
-            Direction::NorthEast => (self.0 - offset, self.1 + offset),
-            Direction::East => (self.0, self.1 + offset),
-            Direction::SouthEast => (self.0 + offset, self.1 + offset),
-            Direction::South => (self.0 + offset, self.1),
-            Direction::SouthWest => (self.0 + offset, self.1 - offset),
-            Direction::West => (self.0, self.1 - offset),
-            Direction::NorthWest => (self.0 - offset, self.1 - offset),
-        }
-    }
-}
+use crate::{
+    geometry::Direction8,
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::{self, Rng},
+    Extras, Solution,
+};
 
-#[derive(Default)]
-struct WordSearch {
-    map: HashMap<Point, char>,
-    max: (isize, isize),
+pub struct WordSearch {
+    grid: Grid<char>,
 }
 
 impl WordSearch {
@@ -44,37 +19,22 @@ impl WordSearch {
         let combinations = HashSet::from(["MSSM", "MMSS", "SMMS", "SSMM"]);
         let mut res = 0;
 
-        for row in 0..=self.max.0 {
-            for col in 0..=self.max.1 {
-                let c = self
-                    .map
-                    .get(&(row, col))
-                    .expect("Should be a letter at this position");
-
-                if *c != 'A' {
-                    continue;
-                }
-
-                let directions = [
-                    Direction::NorthEast,
-                    Direction::SouthEast,
-                    Direction::SouthWest,
-                    Direction::NorthWest,
-                ];
-
-                let cross = directions
-                    .iter()
-                    .map(|direction| {
-                        self.map
-                            .get(&(row, col).offset(1, direction))
-                            .unwrap_or(&'.')
-                    })
-                    .copied()
-                    .collect::<String>();
-
-                if combinations.contains(&cross.as_str()) {
-                    res += 1;
-                }
+        for point in self.grid.find_all(&'A') {
+            let directions = [
+                Direction8::NorthEast,
+                Direction8::SouthEast,
+                Direction8::SouthWest,
+                Direction8::NorthWest,
+            ];
+
+            let cross = directions
+                .iter()
+                .map(|direction| self.grid.get(point.step(*direction)).unwrap_or(&'.'))
+                .copied()
+                .collect::<String>();
+
+            if combinations.contains(&cross.as_str()) {
+                res += 1;
             }
         }
 
@@ -84,101 +44,68 @@ impl WordSearch {
     fn search_for_word(&self, word: &str) -> usize {
         let mut res = 0;
 
-        for row in 0..=self.max.0 {
-            for col in 0..=self.max.1 {
-                let mut invalid = HashSet::new();
-                let directions = [
-                    Direction::North,
-                    Direction::NorthEast,
-                    Direction::East,
-                    Direction::SouthEast,
-                    Direction::South,
-                    Direction::SouthWest,
-                    Direction::West,
-                    Direction::NorthWest,
-                ];
-
-                for (i, c) in word.chars().enumerate() {
-                    for direction in &directions {
-                        if invalid.contains(direction) {
-                            continue;
-                        }
-                        if let Some(next_c) =
-                            self.map.get(&(row, col).offset(i as isize, direction))
-                        {
-                            if *next_c != c {
-                                invalid.insert(direction);
-                            }
-                        } else {
+        for point in self.grid.points() {
+            let mut invalid = HashSet::new();
+            let directions = Direction8::iter().collect::<Vec<_>>();
+
+            for (i, c) in word.chars().enumerate() {
+                for direction in &directions {
+                    if invalid.contains(direction) {
+                        continue;
+                    }
+                    if let Some(next_c) = self.grid.get(point.offset(*direction, i as isize)) {
+                        if *next_c != c {
                             invalid.insert(direction);
                         }
+                    } else {
+                        invalid.insert(direction);
                     }
                 }
-
-                res += directions.len() - invalid.len();
             }
+
+            res += directions.len() - invalid.len();
         }
 
         res
     }
 }
 
-#[aoc_generator(day4)]
-fn parse(input: &str) -> WordSearch {
-    let mut word_search = WordSearch::default();
+pub struct Day4;
 
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, c) in line.trim().chars().enumerate() {
-            word_search.map.insert((row as isize, col as isize), c);
-        }
+impl Solution for Day4 {
+    const DAY: usize = 4;
+    type Input = WordSearch;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(WordSearch {
+            grid: Grid::parse(&Source::new(4, input), Some)?,
+        })
     }
 
-    word_search.max = word_search.map.keys().cloned().max().unwrap_or((0, 0));
-    word_search
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.search_for_word("XMAS")
+    }
 
-#[aoc(day4, part1)]
-fn part1(input: &WordSearch) -> usize {
-    input.search_for_word("XMAS")
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input.search_for_xword()
+    }
 }
 
-#[aoc(day4, part2)]
-fn part2(input: &WordSearch) -> usize {
-    input.search_for_xword()
+impl Extras for Day4 {
+    /// A square of about `size` letters, all from "XMAS" so the word turns up often.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        Some(random::grid(size, |_, _| {
+            *rng.choose(&['X', 'M', 'A', 'S'])
+        }))
+    }
 }
 
+crate::registry::register!(Day4, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST: &str = "
-        MMMSXXMASM
-        MSAMXMSMSA
-        AMXSXMAAMM
-        MSAMASMSMX
-        XMASAMXAMM
-        XXAMMXXAMA
-        SMSMSASXSS
-        SAXAMASAAA
-        MAMMMXMMMM
-        MXMXAXMASX
-    ";
-
-    #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 18);
-    }
 
-    #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 9);
-    }
-
-    #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(4));
-        assert_eq!(part1(input), 2646);
-        assert_eq!(part2(input), 2000);
-    }
+    crate::examples::test_day!(Day4);
 }
@@ -1,9 +1,13 @@
-use std::{
-    cmp::Ordering,
-    collections::{HashMap, HashSet},
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    graph,
+    parser::{ParseError, Source},
+    random::Rng,
+    Extras, Solution,
 };
 
-struct Input {
+pub struct Input {
     rules: HashMap<usize, HashSet<usize>>,
     updates: Vec<Vec<usize>>,
 }
@@ -30,22 +34,25 @@ impl Input {
         valid
     }
 
-    fn order_update(&self, update: &[usize]) -> usize {
-        let mut cloned = update.to_owned();
-
-        cloned.sort_by(|a, b| {
-            if let Some(rule) = self.rules.get(b) {
-                if rule.contains(a) {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            } else {
-                Ordering::Greater
-            }
-        });
+    /// The middle page of `update` once ordered, or `None` if its rules contradict each
+    /// other, so it has no order.
+    fn order_update(&self, update: &[usize]) -> Option<usize> {
+        // Only the rules between pages in the update apply, so the full set of rules may
+        // contain cycles without the update's pages doing so.
+        let ordered = graph::toposort(update.iter().copied(), |page| {
+            update
+                .iter()
+                .copied()
+                .filter(|later| {
+                    self.rules
+                        .get(later)
+                        .is_some_and(|earlier| earlier.contains(page))
+                })
+                .collect::<Vec<_>>()
+        })
+        .ok()?;
 
-        cloned[cloned.len() / 2]
+        Some(ordered[ordered.len() / 2])
     }
 
     fn solve_part1(&self) -> usize {
@@ -66,7 +73,7 @@ impl Input {
             .iter()
             .map(|update| {
                 if !self.update_valid(update) {
-                    self.order_update(update)
+                    self.order_update(update).unwrap_or(0)
                 } else {
                     0
                 }
@@ -90,104 +97,153 @@ impl ContainsAny for HashSet<usize> {
     }
 }
 
-#[aoc_generator(day5)]
-fn parse(input: &str) -> Input {
-    let mut rules = HashMap::new();
-    let mut updates = Vec::new();
-
-    for line in input.trim().lines().map(|line| line.trim()) {
-        if line.contains('|') {
-            let nums = line
-                .split('|')
-                .map(|c| {
-                    c.trim()
-                        .parse::<usize>()
-                        .expect("Should be able to convert next to number")
-                })
-                .collect::<Vec<usize>>();
-
-            let left = nums.first().expect("Should have a number in nums");
-            let right = nums.last().expect("Should have a number in nums");
-
-            rules
-                .entry(*right)
-                .or_insert_with(HashSet::new)
-                .insert(*left);
-        } else if line.contains(',') {
-            updates.push(
-                line.split(',')
-                    .map(|n| {
-                        n.parse()
-                            .expect("Should be able to parse updates as numbers")
-                    })
-                    .collect(),
-            );
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: usize = 5;
+    type Input = Input;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(5, input);
+        let [rule_lines, update_lines] =
+            source.sections_exact("rules and updates separated by a blank line")?;
+        let mut rules = HashMap::new();
+
+        for line in rule_lines {
+            let [left, right] = source.array::<usize, 2>(line, '|', "a page number")?;
+            rules.entry(right).or_insert_with(HashSet::new).insert(left);
         }
+
+        let updates = update_lines
+            .into_iter()
+            .map(|line| source.list(line, ',', "a page number"))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Input { rules, updates })
     }
 
-    Input { rules, updates }
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.solve_part1()
+    }
 
-#[aoc(day5, part1)]
-fn part1(input: &Input) -> usize {
-    input.solve_part1()
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input.solve_part2()
+    }
 }
 
-#[aoc(day5, part2)]
-fn part2(input: &Input) -> usize {
-    input.solve_part2()
+impl Extras for Day5 {
+    /// Rules putting a pool of pages in a complete order, and `size` updates of an odd
+    /// number of them, about half of which are already in order.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let mut pages = (10..100).collect::<Vec<_>>();
+        rng.shuffle(&mut pages);
+        pages.truncate(rng.range(2..24));
+
+        let mut rules = Vec::new();
+        for (i, earlier) in pages.iter().enumerate() {
+            for later in &pages[i + 1..] {
+                rules.push(format!("{}|{}", earlier, later));
+            }
+        }
+        rng.shuffle(&mut rules);
+
+        let updates = (0..size.max(1))
+            .map(|_| {
+                let mut update = pages.clone();
+                rng.shuffle(&mut update);
+                update.truncate(2 * rng.below(pages.len().div_ceil(2)) + 1);
+                if rng.chance(0.5) {
+                    update.sort_by_key(|page| pages.iter().position(|p| p == page));
+                }
+                update
+                    .iter()
+                    .map(|page| page.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>();
+
+        Some(format!("{}\n\n{}", rules.join("\n"), updates.join("\n")))
+    }
 }
 
+crate::registry::register!(Day5, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST: &str = "
-        47|53
-        97|13
-        97|61
-        97|47
-        75|29
-        61|13
-        75|53
-        29|13
-        97|29
-        53|29
-        61|53
-        97|53
-        61|29
-        47|13
-        75|47
-        97|75
-        47|61
-        75|61
-        47|29
-        75|13
-        53|13
-
-        75,47,61,53,29
-        97,61,53,29,13
-        75,29,13
-        75,97,47,61,53
-        61,13,29
-        97,13,75,29,47
-    ";
+    use crate::random;
+
+    crate::examples::test_day!(Day5);
+
+    fn before(input: &Input, earlier: usize, later: usize) -> bool {
+        input
+            .rules
+            .get(&later)
+            .is_some_and(|pages| pages.contains(&earlier))
+    }
+
+    /// Whether no page is followed by one which a rule says should be before it.
+    fn in_order(input: &Input, update: &[usize]) -> bool {
+        (0..update.len()).all(|i| {
+            update[i + 1..]
+                .iter()
+                .all(|later| !before(input, *later, update[i]))
+        })
+    }
 
     #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 143);
+    fn part1_oracle() {
+        random::check_oracle::<Day5>(
+            Day5::part1,
+            |input| {
+                input
+                    .updates
+                    .iter()
+                    .filter(|update| in_order(input, update))
+                    .map(|update| update[update.len() / 2])
+                    .sum()
+            },
+            2000,
+            20,
+        );
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 123);
+    fn part2_oracle() {
+        random::check_oracle::<Day5>(
+            Day5::part2,
+            |input| {
+                input
+                    .updates
+                    .iter()
+                    .filter(|update| !in_order(input, update))
+                    .map(|update| {
+                        // In order, the middle page is the one with half the others before it.
+                        *update
+                            .iter()
+                            .find(|page| {
+                                update
+                                    .iter()
+                                    .filter(|other| before(input, **other, **page))
+                                    .count()
+                                    == update.len() / 2
+                            })
+                            .expect("Update should have a middle page")
+                    })
+                    .sum()
+            },
+            2000,
+            20,
+        );
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(5));
-        assert_eq!(part1(input), 5329);
-        assert_eq!(part2(input), 5833);
+    fn contradictory_rules() {
+        // Pages 1 and 2 each must come before the other, so the update has no order.
+        let input = Day5::parse("1|2\n2|1\n3|4\n\n1,2,3\n4,3,5").unwrap();
+        assert_eq!(input.order_update(&[1, 2, 3]), None);
+        assert_eq!(Day5::part2(&input), 4);
     }
 }
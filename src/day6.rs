@@ -1,50 +1,60 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-type Point = (isize, isize);
+use crate::{
+    animation::{Discard, Recorder, Recording},
+    geometry::{Direction4, Point},
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::{self, Rng},
+    render::{self, Canvas, Colour, Glyph},
+    Extras, Solution,
+};
 
-trait Movable {
-    fn progress(&self, direction: &Direction) -> Point;
-}
+type Map = Grid<Position>;
 
-impl Movable for Point {
-    fn progress(&self, direction: &Direction) -> Point {
-        match direction {
-            Direction::North => (self.0 - 1, self.1),
-            Direction::East => (self.0, self.1 + 1),
-            Direction::South => (self.0 + 1, self.1),
-            Direction::West => (self.0, self.1 - 1),
-        }
-    }
-}
-
-type Map = HashMap<Point, Position>;
-
-struct Input {
+pub struct Input {
     start: Point,
     map: Map,
-    max: Point,
 }
 
 trait Traversable {
-    fn traverse(&mut self, start: Point, start_direction: Direction) -> bool;
+    fn traverse(&mut self, start: Point, start_direction: Direction4) -> bool {
+        self.traverse_recorded(start, start_direction, &mut Discard)
+    }
+
+    /// As [`traverse`](Traversable::traverse), recording the map before every step and
+    /// once the guard has left.
+    fn traverse_recorded(
+        &mut self,
+        start: Point,
+        start_direction: Direction4,
+        recorder: &mut impl Recorder,
+    ) -> bool;
+
     fn visited(&self) -> usize;
 }
 
 impl Traversable for Map {
-    fn traverse(&mut self, start: Point, start_direction: Direction) -> bool {
+    fn traverse_recorded(
+        &mut self,
+        start: Point,
+        start_direction: Direction4,
+        recorder: &mut impl Recorder,
+    ) -> bool {
         let mut point = start;
         let mut direction = start_direction;
 
         loop {
-            let next_point = point.progress(&direction);
-            match self.get_mut(&next_point) {
+            recorder.record(|| draw(self, start, Some((point, direction))));
+            let next_point = point.step(direction);
+            match self.get_mut(next_point) {
                 Some(position) => {
                     if position.visited.contains(&direction) {
                         // We're in a loop
                         return false;
                     } else if position.obstructed {
                         // Turn if obstructed
-                        direction = direction.turn();
+                        direction = direction.turn_right();
                     } else {
                         // Otherwise, progress in the current direction
                         position.visit(&direction);
@@ -55,6 +65,7 @@ impl Traversable for Map {
             }
         }
 
+        recorder.record(|| draw(self, start, None));
         true
     }
 
@@ -65,9 +76,28 @@ impl Traversable for Map {
     }
 }
 
+/// The map drawn as in the puzzle, with the guard if they're still on it.
+fn draw(map: &Map, start: Point, guard: Option<(Point, Direction4)>) -> Canvas {
+    render::draw(map, |point, position| match guard {
+        Some((at, direction)) if at == point => {
+            let symbol = match direction {
+                Direction4::North => '^',
+                Direction4::East => '>',
+                Direction4::South => 'v',
+                Direction4::West => '<',
+            };
+            Glyph::new(symbol, Colour::RED)
+        }
+        _ if position.obstructed => Glyph::new('#', Colour::WHITE),
+        _ if point == start => Glyph::new('X', Colour::GREEN),
+        _ if !position.visited.is_empty() => Glyph::new('X', Colour::YELLOW),
+        _ => Glyph::new('.', Colour::GREY),
+    })
+}
+
 #[derive(Clone)]
 struct Position {
-    visited: HashSet<Direction>,
+    visited: HashSet<Direction4>,
     obstructed: bool,
 }
 
@@ -79,129 +109,172 @@ impl Position {
         }
     }
 
-    pub fn visit(&mut self, direction: &Direction) {
-        self.visited.insert(direction.clone());
+    pub fn visit(&mut self, direction: &Direction4) {
+        self.visited.insert(*direction);
     }
 }
 
-#[derive(Clone, Eq, Hash, PartialEq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
+pub struct Day6;
 
-trait Turnable {
-    fn turn(&self) -> Direction;
-}
+impl Solution for Day6 {
+    const DAY: usize = 6;
+    type Input = Input;
+    type Answer = usize;
 
-impl Turnable for Direction {
-    fn turn(&self) -> Direction {
-        match self {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        }
-    }
-}
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(6, input);
+        let (chars, markers) = Grid::parse_marked(&source, |c| c == '^', Some)?;
+        let start = markers.unique(&source, '^', "a starting point '^'")?;
 
-#[aoc_generator(day6)]
-fn parse(input: &str) -> Input {
-    let mut start = (0, 0);
-    let mut map = HashMap::new();
-
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, c) in line.trim().chars().enumerate() {
-            let position = match c {
-                '^' => {
-                    start = (row as isize, col as isize);
-                    Position {
-                        visited: HashSet::from([Direction::North]),
-                        obstructed: false,
-                    }
-                }
-                '#' => Position::new(true),
-                _ => Position::new(false),
-            };
-            map.insert((row as isize, col as isize), position);
-        }
-    }
+        let mut map = chars.map(|c| Position::new(*c == '#'));
+        map[start].visit(&Direction4::North);
 
-    let max = *map.keys().max().expect("Map should have a maximum point");
+        Ok(Input { start, map })
+    }
 
-    Input { start, map, max }
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        // A guard stuck in a loop still only ever visits the positions on it.
+        let mut map = input.map.clone();
+        map.traverse(input.start, Direction4::North);
+        map.visited()
+    }
 
-#[aoc(day6, part1)]
-fn part1(input: &Input) -> usize {
-    let mut map = input.map.clone();
-    match map.traverse(input.start, Direction::North) {
-        true => map.visited(),
-        false => panic!("Did not traverse map correctly"),
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input
+            .map
+            .points()
+            .filter(|point| input.loops_with_obstruction(*point))
+            .count()
     }
 }
 
-#[aoc(day6, part2)]
-fn part2(input: &Input) -> usize {
-    let mut res = 0;
-
-    for row in 0..=input.max.0 {
-        for col in 0..=input.max.1 {
-            if (row, col) == input.start {
-                continue;
+impl Extras for Day6 {
+    /// A square lab of about `size` positions, a few of them obstructed, with the guard
+    /// somewhere on it.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let side = random::side(size);
+        let start = rng.below(side * side);
+        Some(random::grid(size, |row, col| {
+            if row * side + col == start {
+                '^'
+            } else if rng.chance(0.06) {
+                '#'
+            } else {
+                '.'
             }
+        }))
+    }
 
-            let mut map = input.map.clone();
+    /// The guard's path, drawn as in the puzzle.
+    fn render(input: &Self::Input) -> Option<Canvas> {
+        let mut map = input.map.clone();
+        map.traverse(input.start, Direction4::North);
+        Some(draw(&map, input.start, None))
+    }
 
-            if let Some(position) = map.get_mut(&(row, col)) {
-                if position.obstructed {
-                    continue;
-                }
-                position.obstructed = true;
-                if !map.traverse(input.start, Direction::North) {
-                    res += 1;
-                }
-            }
+    /// The guard walking their route.
+    fn animate(input: &Self::Input, recording: &mut Recording) -> bool {
+        input
+            .map
+            .clone()
+            .traverse_recorded(input.start, Direction4::North, recording);
+        true
+    }
+}
+
+impl Input {
+    /// Whether adding an obstruction at `point` traps the guard in a loop.
+    fn loops_with_obstruction(&self, point: Point) -> bool {
+        if point == self.start || self.map[point].obstructed {
+            return false;
         }
+
+        let mut map = self.map.clone();
+        map[point].obstructed = true;
+        !map.traverse(self.start, Direction4::North)
     }
+}
+
+#[cfg(feature = "parallel")]
+impl Day6 {
+    /// `part2`, simulating each candidate obstruction in parallel.
+    pub fn part2_parallel(input: &Input) -> usize {
+        use rayon::prelude::*;
 
-    res
+        input
+            .map
+            .points()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .filter(|point| input.loops_with_obstruction(*point))
+            .count()
+    }
 }
 
+#[cfg(not(feature = "parallel"))]
+crate::registry::register!(Day6, part1, part2);
+#[cfg(feature = "parallel")]
+crate::registry::register!(Day6, part1, part2_parallel | part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST: &str = "
-        ....#.....
-        .........#
-        ..........
-        ..#.......
-        .......#..
-        ..........
-        .#..^.....
-        ........#.
-        #.........
-        ......#...
-    ";
+    use crate::examples;
+
+    crate::examples::test_day!(Day6);
+
+    #[test]
+    fn render_path() {
+        let input = Day6::parse(&examples::input(6, "example")).unwrap();
+        let canvas = Day6::render(&input).unwrap();
+        assert_eq!(
+            render::plain(&canvas),
+            "\
+....#.....
+....XXXXX#
+....X...X.
+..#.X...X.
+..XXXXX#X.
+..X.X.X.X.
+.#XXXXXXX.
+.XXXXXXX#.
+#XXXXXXX..
+......#X..
+"
+        );
+    }
 
     #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 41);
+    fn animate_path() {
+        let mut recording = Recording::default();
+        let input = Day6::parse(&examples::input(6, "example")).unwrap();
+        assert!(Day6::animate(&input, &mut recording));
+
+        let frames = recording.frames();
+        assert_eq!(render::plain(&frames[0]).lines().nth(6), Some(".#..^....."));
+        assert_eq!(render::plain(&frames[5]).lines().nth(1), Some("....^....#"));
+        assert_eq!(render::plain(&frames[6]).lines().nth(1), Some("....>....#"));
+        assert_eq!(frames.last(), Day6::render(&input).as_ref());
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 6);
+    fn guard_loop() {
+        let input = Day6::parse(".#..\n...#\n#^..\n..#.").unwrap();
+        assert_eq!(Day6::part1(&input), 4);
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(6));
-        assert_eq!(part1(input), 5208);
-        // assert_eq!(part2(input), 1972);
+    fn parse_error() {
+        let error = Day6::parse("..#\n...")
+            .err()
+            .expect("Expected a parse error");
+        assert_eq!(error.reason, "expected a starting point '^'");
+        assert!(Day6::parse("..^\n.").is_err());
+
+        let error = Day6::parse(".^.\n..^")
+            .err()
+            .expect("Expected a parse error");
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.reason, "expected only one '^'");
     }
 }
@@ -1,21 +1,35 @@
-struct Equation {
+use crate::{
+    math::Digits,
+    parser::{ParseError, Separator, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
+pub struct Equation {
     target: isize,
     numbers: Vec<isize>,
 }
 
 impl Equation {
     fn solve(&self, acc: isize, index: usize, concat_enabled: bool) -> bool {
+        // Every number is positive, so no operator ever brings an overshoot back down.
+        if acc > self.target {
+            return false;
+        }
         match self.numbers.get(index) {
+            // A sum, product or concatenation which overflows is far beyond any target.
             Some(number) => {
-                if self.solve(acc + number, index + 1, concat_enabled)
-                    || self.solve(acc * number, index + 1, concat_enabled)
+                if acc
+                    .checked_add(*number)
+                    .is_some_and(|sum| self.solve(sum, index + 1, concat_enabled))
+                    || acc
+                        .checked_mul(*number)
+                        .is_some_and(|product| self.solve(product, index + 1, concat_enabled))
                 {
                     true
                 } else if concat_enabled {
-                    let combined = format!("{}{}", acc, number)
-                        .parse::<isize>()
-                        .expect("Error concatenating numbers");
-                    self.solve(combined, index + 1, concat_enabled)
+                    acc.checked_concat(*number)
+                        .is_some_and(|combined| self.solve(combined, index + 1, concat_enabled))
                 } else {
                     false
                 }
@@ -26,76 +40,142 @@ impl Equation {
     }
 }
 
-#[aoc_generator(day7)]
-fn parse(input: &str) -> Vec<Equation> {
-    input
-        .trim()
-        .lines()
-        .map(|line| {
-            let (target, numbers) = line
-                .trim()
-                .split_once(": ")
-                .expect("Error splitting input line");
-            Equation {
-                target: target.parse().expect("Error parsing target"),
-                numbers: numbers
-                    .split_whitespace()
-                    .map(|number| number.parse().expect("Error parsing numbers"))
-                    .collect(),
-            }
-        })
-        .collect()
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: usize = 7;
+    type Input = Vec<Equation>;
+    /// Wide enough that no input small enough to read can overflow it, whatever its targets.
+    type Answer = i128;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(7, input);
+        source
+            .lines()
+            .map(|line| {
+                let (target, numbers) = source.key_values::<_, isize>(
+                    line,
+                    "a target",
+                    Separator::Whitespace,
+                    "a number",
+                )?;
+                if let Some(index) = numbers.iter().position(|number| *number < 1) {
+                    let token = line
+                        .split_once(':')
+                        .and_then(|(_, numbers)| numbers.split_whitespace().nth(index));
+                    return Err(source.error(token.unwrap_or(line), "expected a positive number"));
+                }
+                Ok(Equation { target, numbers })
+            })
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input
+            .iter()
+            .filter(|equation| equation.solve(0, 0, false))
+            .map(|equation| equation.target as i128)
+            .sum()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input
+            .iter()
+            .filter(|equation| equation.solve(0, 0, true))
+            .map(|equation| equation.target as i128)
+            .sum()
+    }
 }
 
-#[aoc(day7, part1)]
-fn part1(input: &[Equation]) -> isize {
-    input
-        .iter()
-        .filter(|equation| equation.solve(0, 0, false))
-        .map(|equation| equation.target)
-        .sum()
+impl Extras for Day7 {
+    /// `size` equations of two to twelve numbers, about half of which can be made true.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let lines = (0..size.max(1))
+            .map(|_| {
+                let numbers = (0..rng.range(2..13))
+                    .map(|_| rng.range(1..1000) as isize)
+                    .collect::<Vec<_>>();
+                let reachable =
+                    match rng.chance(0.5) {
+                        true => numbers[1..].iter().try_fold(numbers[0], |acc, number| {
+                            match rng.below(3) {
+                                0 => acc.checked_add(*number),
+                                1 => acc.checked_mul(*number),
+                                _ => acc.checked_concat(*number),
+                            }
+                        }),
+                        false => None,
+                    };
+                let target = reachable.unwrap_or_else(|| rng.range(1..1_000_000_000_000) as isize);
+                let numbers = numbers
+                    .iter()
+                    .map(|number| number.to_string())
+                    .collect::<Vec<_>>();
+                format!("{}: {}", target, numbers.join(" "))
+            })
+            .collect::<Vec<_>>();
+        Some(lines.join("\n"))
+    }
 }
 
-#[aoc(day7, part2)]
-fn part2(input: &[Equation]) -> isize {
-    input
-        .iter()
-        .filter(|equation| equation.solve(0, 0, true))
-        .map(|equation| equation.target)
-        .sum()
+#[cfg(feature = "parallel")]
+impl Day7 {
+    fn calibrate_parallel(input: &[Equation], concat_enabled: bool) -> i128 {
+        use rayon::prelude::*;
+
+        input
+            .par_iter()
+            .filter(|equation| equation.solve(0, 0, concat_enabled))
+            .map(|equation| equation.target as i128)
+            .sum()
+    }
+
+    /// `part1`, trying equations in parallel.
+    pub fn part1_parallel(input: &[Equation]) -> i128 {
+        Self::calibrate_parallel(input, false)
+    }
+
+    /// `part2`, trying equations in parallel.
+    pub fn part2_parallel(input: &[Equation]) -> i128 {
+        Self::calibrate_parallel(input, true)
+    }
 }
 
+#[cfg(not(feature = "parallel"))]
+crate::registry::register!(Day7, part1, part2);
+#[cfg(feature = "parallel")]
+crate::registry::register!(Day7, part1_parallel | part1, part2_parallel | part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST: &str = "
-        190: 10 19
-        3267: 81 40 27
-        83: 17 5
-        156: 15 6
-        7290: 6 8 6 15
-        161011: 16 10 13
-        192: 17 8 14
-        21037: 9 7 18 13
-        292: 11 6 16 20
-    ";
+
+    crate::examples::test_day!(Day7);
 
     #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST)), 3749);
+    fn large_targets() {
+        let input = Day7::parse(
+            "9223372036854775807: 9223372036854775807\n9223372036854775807: 9223372036854775807",
+        )
+        .unwrap();
+        assert_eq!(Day7::part1(&input), 2 * isize::MAX as i128);
+
+        let Err(error) = Day7::parse("3: 1 2\n6: 1 0 5") else {
+            panic!("a zero should be rejected");
+        };
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 6, "0"));
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST)), 11387);
+    fn long_equation() {
+        // Only ever multiples of seven, so unsolvable, and every way of trying has to fail.
+        let input = Day7::parse(&format!("239:{}", " 7".repeat(34))).unwrap();
+        assert_eq!(Day7::part2(&input), 0);
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(7));
-        assert_eq!(part1(input), 3119088655389);
-        assert_eq!(part2(input), 264184041398847);
+    fn overflow() {
+        let input = Day7::parse("1: 999 999 999 999 999 999 999 999").unwrap();
+        assert_eq!(Day7::part2(&input), 0);
     }
 }
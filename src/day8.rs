@@ -1,176 +1,170 @@
-use std::{
-    cmp::max,
-    collections::{HashMap, HashSet},
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    geometry::Point,
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::{self, Rng},
+    render::{self, Canvas, Colour, Glyph},
+    Extras, Solution,
 };
 
-type Point = (isize, isize);
 type Antennas = HashMap<char, Vec<Point>>;
 
-struct Input {
+pub struct Input {
     antennas: Antennas,
-    max_point: Point,
+    grid: Grid<char>,
 }
 
-trait Constrainable {
-    fn valid(&self, max_point: &Point) -> bool;
-}
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: usize = 8;
+    type Input = Input;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        // Antennas are tuned to a letter or digit, and anything else is a mistake.
+        let (grid, markers) = Grid::parse_marked(
+            &Source::new(8, input),
+            |c| c.is_ascii_alphanumeric(),
+            |c| (c == '.' || c.is_ascii_alphanumeric()).then_some(c),
+        )?;
+
+        Ok(Input {
+            antennas: markers.into_groups(),
+            grid,
+        })
+    }
 
-impl Constrainable for Point {
-    fn valid(&self, max_point: &Point) -> bool {
-        self.0 >= 0 && self.0 <= max_point.0 && self.1 >= 0 && self.1 <= max_point.1
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.antinodes().len()
     }
-}
 
-#[aoc_generator(day8)]
-fn parse(input: &str) -> Input {
-    let mut antennas = HashMap::new();
-    let mut max_point = Point::default();
+    fn part2(input: &Self::Input) -> Self::Answer {
+        input.resonant_antinodes().len()
+    }
+}
 
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, c) in line.trim().chars().enumerate() {
-            let point = (row as isize, col as isize);
-            if c != '.' {
-                antennas.entry(c).or_insert(Vec::new()).push(point);
+impl Extras for Day8 {
+    /// A square map of about `size` positions with an antenna on one in twenty, each
+    /// tuned to any letter or digit.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        let frequencies = ('0'..='9')
+            .chain('a'..='z')
+            .chain('A'..='Z')
+            .collect::<Vec<_>>();
+        Some(random::grid(size, |_, _| {
+            if rng.chance(0.05) {
+                *rng.choose(&frequencies)
+            } else {
+                '.'
             }
-            max_point = max(max_point, point);
-        }
+        }))
     }
 
-    Input {
-        antennas,
-        max_point,
+    /// The antennas and part 1's antinodes, drawn as in the puzzle.
+    fn render(input: &Self::Input) -> Option<Canvas> {
+        let antinodes = input.antinodes();
+        let mut frequencies = input.antennas.keys().copied().collect::<Vec<_>>();
+        frequencies.sort();
+        Some(render::draw(&input.grid, |point, c| {
+            match frequencies.iter().position(|frequency| frequency == c) {
+                Some(index) => Glyph::new(*c, Colour::palette(index)),
+                None if antinodes.contains(&point) => Glyph::new('#', Colour::WHITE),
+                None => Glyph::new('.', Colour::GREY),
+            }
+        }))
     }
 }
 
-#[aoc(day8, part1)]
-fn part1(input: &Input) -> usize {
-    let mut antinodes = HashSet::new();
-
-    for (_, antennas) in input.antennas.iter() {
-        for (i, a1) in antennas.iter().enumerate() {
-            for antenna in antennas.iter().skip(i + 1) {
-                let a2 = antenna;
-                let drow = a1.0 - a2.0;
-                let dcol = a1.1 - a2.1;
-                let an1 = (a1.0 + drow, a1.1 + dcol);
-                let an2 = (a2.0 - drow, a2.1 - dcol);
-
-                for an in [an1, an2] {
-                    if an.valid(&input.max_point) {
-                        antinodes.insert(an);
+impl Input {
+    fn antinodes(&self) -> HashSet<Point> {
+        let mut antinodes = HashSet::new();
+
+        for (_, antennas) in self.antennas.iter() {
+            for (i, a1) in antennas.iter().enumerate() {
+                for antenna in antennas.iter().skip(i + 1) {
+                    let a2 = antenna;
+                    let delta = *a1 - *a2;
+                    let an1 = *a1 + delta;
+                    let an2 = *a2 - delta;
+
+                    for an in [an1, an2] {
+                        if self.grid.contains(an) {
+                            antinodes.insert(an);
+                        }
                     }
                 }
             }
         }
-    }
 
-    antinodes.len()
-}
+        antinodes
+    }
 
-#[aoc(day8, part2)]
-fn part2(input: &Input) -> usize {
-    let mut antinodes = HashSet::new();
+    fn resonant_antinodes(&self) -> HashSet<Point> {
+        let mut antinodes = HashSet::new();
 
-    for (_, antennas) in input.antennas.iter() {
-        for (i, a1) in antennas.iter().enumerate() {
-            for a2 in antennas.iter().skip(i + 1) {
-                let drow = a1.0 - a2.0;
-                let dcol = a1.1 - a2.1;
+        for (_, antennas) in self.antennas.iter() {
+            for (i, a1) in antennas.iter().enumerate() {
+                for a2 in antennas.iter().skip(i + 1) {
+                    let delta = *a1 - *a2;
 
-                let mut an = *a1;
-                while an.valid(&input.max_point) {
-                    antinodes.insert(an);
-                    an = (an.0 + drow, an.1 + dcol)
-                }
+                    let mut an = *a1;
+                    while self.grid.contains(an) {
+                        antinodes.insert(an);
+                        an += delta
+                    }
 
-                an = *a2;
-                while an.valid(&input.max_point) {
-                    antinodes.insert(an);
-                    an = (an.0 - drow, an.1 - dcol)
+                    an = *a2;
+                    while self.grid.contains(an) {
+                        antinodes.insert(an);
+                        an -= delta
+                    }
                 }
             }
         }
-    }
 
-    antinodes.len()
+        antinodes
+    }
 }
 
+crate::registry::register!(Day8, part1, part2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
-
-    const TEST1: &str = "
-        ..........
-        ..........
-        ..........
-        ....a.....
-        ..........
-        .....a....
-        ..........
-        ..........
-        ..........
-        ..........
-    ";
-
-    const TEST2: &str = "
-        ..........
-        ..........
-        ..........
-        ....a.....
-        ........a.
-        .....a....
-        ..........
-        ......A...
-        ..........
-        ..........
-    ";
-
-    const TEST3: &str = "
-        ............
-        ........0...
-        .....0......
-        .......0....
-        ....0.......
-        ......A.....
-        ............
-        ............
-        ........A...
-        .........A..
-        ............
-        ............
-    ";
-
-    const TEST4: &str = "
-        T.........
-        ...T......
-        .T........
-        ..........
-        ..........
-        ..........
-        ..........
-        ..........
-        ..........
-        ..........  
-    ";
+    use crate::examples;
 
-    #[test]
-    fn part1_example() {
-        assert_eq!(part1(&parse(TEST1)), 2);
-        assert_eq!(part1(&parse(TEST2)), 4);
-        assert_eq!(part1(&parse(TEST3)), 14);
-    }
+    crate::examples::test_day!(Day8);
 
     #[test]
-    fn part2_example() {
-        assert_eq!(part2(&parse(TEST4)), 9);
-        assert_eq!(part2(&parse(TEST3)), 34);
+    fn parse_error() {
+        let error = Day8::parse("a.\n.:").err().expect("Expected a parse error");
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.reason, "unexpected character");
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(8));
-        assert_eq!(part1(input), 240);
-        assert_eq!(part2(input), 955);
+    fn render_antinodes() {
+        let input = Day8::parse(&examples::input(8, "larger")).unwrap();
+        let canvas = Day8::render(&input).unwrap();
+        assert_eq!(
+            render::plain(&canvas),
+            "\
+......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+"
+        );
     }
 }
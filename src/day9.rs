@@ -1,14 +1,34 @@
 use num_integer::Integer;
 
+use crate::{
+    animation::{Discard, Recorder, Recording},
+    grid::Grid,
+    parser::{ParseError, Source},
+    random::Rng,
+    render::{Canvas, Colour, Glyph},
+    Extras, Solution,
+};
+
+/// About how many frames to record of the disk being fragmented. Each is the whole disk,
+/// tens of thousands of blocks for a real input, so recording every move would take
+/// hundreds of megabytes.
+const FRAMES: usize = 100;
+
 #[derive(Clone, PartialEq)]
-enum Block {
+pub enum Block {
     Fragment(usize),
     Empty,
 }
 
-trait Disk {
+trait Disk: Sized {
     fn checksum(&self) -> usize;
-    fn fragment(self) -> Self;
+    fn fragment(self) -> Self {
+        self.fragment_recorded(&mut Discard)
+    }
+
+    /// As [`fragment`](Disk::fragment), recording the disk before any block moves, after
+    /// every so many do, so there are about [`FRAMES`] in all, and once they all have.
+    fn fragment_recorded(self, recorder: &mut impl Recorder) -> Self;
 }
 
 impl Disk for Vec<Block> {
@@ -22,9 +42,13 @@ impl Disk for Vec<Block> {
             .sum()
     }
 
-    fn fragment(mut self) -> Self {
+    fn fragment_recorded(mut self, recorder: &mut impl Recorder) -> Self {
+        recorder.record(|| draw(&self));
+        let gaps = self.iter().filter(|block| **block == Block::Empty).count();
+        let every = gaps.div_ceil(FRAMES).max(1);
+        let mut moved = 0;
         let mut l = 0_usize;
-        let mut r = self.len() - 1;
+        let mut r = self.len().saturating_sub(1);
 
         while l < r {
             let left = self.get(l).expect("Expected a left block");
@@ -37,79 +61,186 @@ impl Disk for Vec<Block> {
 
             if let Block::Empty = left {
                 self.swap(l, r);
+                moved += 1;
+                if moved % every == 0 {
+                    recorder.record(|| draw(&self));
+                }
                 r -= 1;
             }
 
             l += 1;
         }
 
+        if moved % every != 0 {
+            recorder.record(|| draw(&self));
+        }
         self
     }
 }
 
-struct _File {
-    id: usize,
-    moved: bool,
+/// The disk as a single row, drawn as in the puzzle with each file's id mod 10.
+fn draw(disk: &[Block]) -> Canvas {
+    Grid::new(
+        disk.len(),
+        1,
+        disk.iter()
+            .map(|block| match block {
+                Block::Fragment(id) => Glyph::new(
+                    char::from_digit((id % 10) as u32, 10).expect("Should be a single digit"),
+                    Colour::palette(*id),
+                ),
+                Block::Empty => Glyph::new('.', Colour::GREY),
+            })
+            .collect(),
+    )
 }
 
-impl _File {
-    fn _new(id: usize) -> Self {
-        Self { id, moved: false }
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: usize = 9;
+    type Input = Vec<Block>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(9, input);
+        let disk_map = input.trim();
+        let mut id = 0;
+
+        let lengths = disk_map
+            .char_indices()
+            .map(|(i, c)| {
+                c.to_digit(10)
+                    .ok_or_else(|| source.error(&disk_map[i..i + c.len_utf8()], "expected a digit"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(lengths
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, n)| {
+                std::iter::repeat_n(
+                    if index.is_even() {
+                        id += 1;
+                        Block::Fragment(id - 1)
+                    } else {
+                        Block::Empty
+                    },
+                    n as usize,
+                )
+                .collect::<Vec<Block>>()
+            })
+            .collect())
     }
-}
 
-#[aoc_generator(day9)]
-fn parse(input: &str) -> Vec<Block> {
-    let mut id = 0;
-
-    input
-        .trim()
-        .chars()
-        .enumerate()
-        .map(|(index, c)| (index, c.to_digit(10).expect("Failed to cast char to usize")))
-        .flat_map(|(index, n)| {
-            std::iter::repeat_n(
-                if index.is_even() {
-                    id += 1;
-                    Block::Fragment(id - 1)
-                } else {
-                    Block::Empty
-                },
-                n as usize,
-            )
-            .collect::<Vec<Block>>()
-        })
-        .collect()
-}
+    fn part1(input: &Self::Input) -> Self::Answer {
+        input.to_owned().fragment().checksum()
+    }
 
-#[aoc(day9, part1)]
-fn part1(input: &[Block]) -> usize {
-    input.to_owned().fragment().checksum()
+    /// Not solved yet, so not registered.
+    fn part2(_input: &Self::Input) -> Self::Answer {
+        todo!()
+    }
 }
 
-#[aoc(day9, part2)]
-fn part2(_input: &[Block]) -> usize {
-    todo!()
+impl Extras for Day9 {
+    /// A disk map of `size` digits, with no empty files.
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        Some(
+            (0..size.max(1))
+                .map(|index| {
+                    let length = if index.is_even() {
+                        rng.range(1..10)
+                    } else {
+                        rng.below(10)
+                    };
+                    char::from_digit(length as u32, 10).expect("Length should be one digit")
+                })
+                .collect(),
+        )
+    }
+
+    /// Blocks moving one at a time from the end of the disk to the first free space.
+    fn animate(input: &Self::Input, recording: &mut Recording) -> bool {
+        input.to_owned().fragment_recorded(recording);
+        true
+    }
 }
 
+crate::registry::register!(Day9, part1);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser;
+    use crate::{examples, random, render};
     use test_case::test_case;
 
-    const INPUT_1: &str = "12345";
-    const INPUT_2: &str = "2333133121414131402";
+    crate::examples::test_day!(Day9);
 
-    #[test_case(INPUT_1, 60; "input_1")]
-    #[test_case(INPUT_2, 1928; "input_2")]
-    fn part1_example(input: &str, want: usize) {
-        assert_eq!(part1(&parse(input)), want);
+    #[test_case(""; "empty")]
+    #[test_case("0"; "empty_file")]
+    #[test_case("1"; "one_block")]
+    fn part1_tiny(input: &str) {
+        assert_eq!(Day9::part1(&Day9::parse(input).unwrap()), 0);
+    }
+
+    #[test]
+    fn animate_fragment() {
+        let mut recording = Recording::default();
+        assert!(Day9::animate(
+            &Day9::parse(&examples::input(9, "small")).unwrap(),
+            &mut recording
+        ));
+
+        assert_eq!(
+            recording
+                .frames()
+                .iter()
+                .map(render::plain)
+                .collect::<String>(),
+            "0..111....22222\n\
+             02.111....2222.\n\
+             022111....222..\n\
+             0221112...22...\n\
+             02211122..2....\n\
+             022111222......\n"
+        );
+    }
+
+    #[test]
+    fn animate_long_disk() {
+        let input = Day9::generate(&mut random::Rng::new(9), 2000).unwrap();
+        let disk = Day9::parse(&input).unwrap();
+        let mut recording = Recording::default();
+        assert!(Day9::animate(&disk, &mut recording));
+
+        let frames = recording.frames();
+        assert!(frames.len() > FRAMES / 2 && frames.len() <= FRAMES + 2);
+        let last = render::plain(frames.last().unwrap());
+        let fragmented = render::plain(&draw(&disk.clone().fragment()));
+        assert_eq!(last, fragmented);
     }
 
     #[test]
-    fn mainline() {
-        let input = &parse(&parser::load_input(9));
-        assert_eq!(part1(input), 6356833654075);
+    fn part1_oracle() {
+        random::check_oracle::<Day9>(
+            Day9::part1,
+            |input| {
+                // Move the last file block into the first gap until no gap comes before it.
+                let mut disk = input.clone();
+                while let (Some(gap), Some(last)) = (
+                    disk.iter().position(|block| *block == Block::Empty),
+                    disk.iter().rposition(|block| *block != Block::Empty),
+                ) {
+                    if gap > last {
+                        break;
+                    }
+                    disk.swap(gap, last);
+                }
+                disk.checksum()
+            },
+            2000,
+            40,
+        );
     }
 }
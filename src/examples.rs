@@ -0,0 +1,213 @@
+use std::{collections::BTreeMap, error::Error, fmt, fs, io, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::{
+    answers::{Answer, AnswersError},
+    parser::{self, InputError},
+};
+
+/// A small input from a puzzle's description, with whichever answers it gives for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Example {
+    /// The name of the example's file, without its extension.
+    pub name: String,
+    pub input: String,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+}
+
+/// One example's entry in the manifest.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Expected {
+    part1: Option<Answer>,
+    part2: Option<Answer>,
+}
+
+#[derive(Debug)]
+pub enum ExampleError {
+    Manifest(AnswersError),
+    Input(InputError),
+}
+
+impl fmt::Display for ExampleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExampleError::Manifest(error) => write!(f, "examples: {}", error),
+            ExampleError::Input(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ExampleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExampleError::Manifest(error) => Some(error),
+            ExampleError::Input(error) => Some(error),
+        }
+    }
+}
+
+impl Example {
+    /// The directory holding a year's examples, one subdirectory per day, and the manifest
+    /// of their answers.
+    pub fn dir(year: usize) -> PathBuf {
+        parser::input_dir().join(year.to_string()).join("examples")
+    }
+
+    pub fn path(year: usize, day: usize, name: &str) -> PathBuf {
+        Self::dir(year)
+            .join(format!("day{}", day))
+            .join(format!("{}.txt", name))
+    }
+
+    /// Load every example the manifest lists for a day, in name order. A day missing from
+    /// the manifest, or a missing manifest, has none.
+    pub fn load(year: usize, day: usize) -> Result<Vec<Self>, ExampleError> {
+        let text = match fs::read_to_string(Self::dir(year).join("answers.toml")) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(ExampleError::Manifest(AnswersError::Io(error))),
+        };
+        let mut days: BTreeMap<String, BTreeMap<String, Expected>> = toml::from_str(&text)
+            .map_err(|error| ExampleError::Manifest(AnswersError::Malformed(error)))?;
+
+        days.remove(&format!("day{}", day))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, expected)| {
+                let path = Self::path(year, day, &name);
+                let input = fs::read_to_string(&path).map_err(|source| {
+                    ExampleError::Input(match source.kind() {
+                        io::ErrorKind::NotFound => InputError::Missing { year, day, path },
+                        _ => InputError::Unreadable { path, source },
+                    })
+                })?;
+                Ok(Self {
+                    name,
+                    input,
+                    part1: expected.part1,
+                    part2: expected.part2,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether this is still the empty example `new-day` leaves, with no input or no
+    /// answers yet, which has nothing to check.
+    pub fn is_placeholder(&self) -> bool {
+        self.input.trim().is_empty() || (self.part1.is_none() && self.part2.is_none())
+    }
+
+    /// The answer the puzzle gives for `part` of this example, if it gives one.
+    pub fn answer(&self, part: usize) -> Option<&Answer> {
+        match part {
+            1 => self.part1.as_ref(),
+            2 => self.part2.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// The input of one of this year's examples, for tests which need it for more than its
+/// answers. Panics if it isn't there.
+#[cfg(test)]
+pub(crate) fn input(day: usize, name: &str) -> String {
+    Example::load(parser::YEAR, day)
+        .unwrap()
+        .into_iter()
+        .find(|example| example.name == name)
+        .unwrap_or_else(|| panic!("day {} should have an example named {}", day, name))
+        .input
+}
+
+/// Check every implementation of each part of `day` against every answer its examples
+/// give, skipping placeholders.
+#[cfg(test)]
+pub(crate) fn check_examples(day: usize) {
+    let day = crate::registry::day(day).expect("Day should be registered");
+    let examples = Example::load(parser::YEAR, day.day).unwrap();
+    assert!(!examples.is_empty(), "day {} has no examples", day.day);
+
+    for example in examples.iter().filter(|example| !example.is_placeholder()) {
+        for part in 1..=day.parts.len() {
+            let Some(expected) = example.answer(part) else {
+                continue;
+            };
+            for implementation in day.implementations(part) {
+                let actual = (implementation.run)(&example.input)
+                    .unwrap_or_else(|error| panic!("example {}: {}", example.name, error));
+                assert_eq!(
+                    actual, *expected,
+                    "day {} {} on example {}",
+                    day.day, implementation.name, example.name
+                );
+            }
+        }
+    }
+}
+
+/// Check the default implementation of each part of `day` against the recorded answers
+/// for the real input, skipping parts with no input or answer.
+#[cfg(test)]
+pub(crate) fn check_mainline(day: usize) {
+    use crate::{
+        answers::Answers,
+        runner::{self, Status},
+    };
+
+    let answers = Answers::load(parser::YEAR).unwrap();
+    let day = crate::registry::day(day).expect("Day should be registered");
+    for verification in runner::verify(&day, &answers).unwrap() {
+        match verification.status {
+            Status::Correct => (),
+            Status::Unanswered | Status::MissingInput => eprintln!(
+                "skipping day {} part {}: {:?}",
+                verification.day, verification.part, verification.status
+            ),
+            Status::Incorrect | Status::Malformed => panic!(
+                "day {} part {}: expected {:?}, got {:?}",
+                verification.day, verification.part, verification.expected, verification.actual
+            ),
+        }
+    }
+}
+
+/// Generate a day's `examples` test, checking it against the answers in the examples
+/// manifest, and its `mainline` test, checking it against the answers for the real input.
+#[cfg(test)]
+macro_rules! test_day {
+    ($solution:ident) => {
+        #[test]
+        fn examples() {
+            $crate::examples::check_examples(<$solution as $crate::Solution>::DAY);
+        }
+
+        #[test]
+        fn mainline() {
+            $crate::examples::check_mainline(<$solution as $crate::Solution>::DAY);
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use test_day;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_day_has_examples() {
+        for day in crate::registry::days() {
+            let examples = Example::load(parser::YEAR, day.day).unwrap();
+            assert!(!examples.is_empty(), "day {} has no examples", day.day);
+            // A new day's examples are placeholders until its puzzle is read.
+            if examples.iter().all(Example::is_placeholder) {
+                eprintln!("day {} has only placeholder examples", day.day);
+            }
+        }
+        assert!(Example::load(parser::YEAR, 26).unwrap().is_empty());
+    }
+}
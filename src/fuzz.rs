@@ -0,0 +1,226 @@
+//! Checks that every day rejects, or solves, any input it's given, rather than panicking
+//! or hanging.
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use crate::{
+    animation::Recording,
+    examples::Example,
+    parser,
+    random::Rng,
+    registry::{self, Day},
+};
+
+/// How long one parse and solve may take before it's considered to have hung.
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Inputs which are wrong, or at least strange, for every day.
+const ODDITIES: &[&str] = &[
+    "",
+    "\n",
+    " \t\n\n  \n",
+    "\0",
+    "\r\n\r\n",
+    "é",
+    "🎄\n🎄🎄",
+    "0",
+    "1",
+    "-1",
+    "99999999999999999999999999",
+    "9999999999999",
+    "1: 999 999 999 999 999 999 999 999",
+    // Well formed, but with numbers as large as their types hold.
+    "18446744073709551615   18446744073709551615\n1   18446744073709551615",
+    "9223372036854775807 -9223372036854775808",
+    "-9223372036854775808 9223372036854775807 0",
+    "mul(9999999999,9999999999)mul(999,999)",
+    "9223372036854775807: 9223372036854775807\n9223372036854775807: 9223372036854775807",
+    "-9223372036854775808: 9223372036854775807 9223372036854775807",
+    "1 2",
+    "1 2 3\n4",
+    "1|2",
+    "1|2\n\n",
+    "\n\n1,2,3",
+    "1: ",
+    ": 1 2",
+    "1:",
+    "mul(",
+    "mul(2,3",
+    "^",
+    "^^",
+    "#",
+    "#^\n",
+    ".#\n#",
+    "^.\n.^",
+    ".\n..\n...",
+    "0\n",
+    "a",
+    "A\u{301}",
+];
+
+/// Inputs far bigger than any real one, which should be rejected, or solved, without
+/// their size holding anything up. Otherwise valid inputs scale with each day's own
+/// algorithm instead.
+fn huge() -> Vec<String> {
+    vec![
+        // A number of a million digits.
+        format!("1{}\n1", "0".repeat(1 << 20)),
+        // Grids with a very long row, or very many, which are ragged at the end.
+        format!("{}\n.", ".".repeat(1 << 20)),
+        format!("{}xx", "x\n".repeat(1 << 18)),
+        // An equation with too many numbers to ever solve, broken at its end.
+        format!("1: {}x", "1 ".repeat(1 << 18)),
+        // Many of the largest pairs of IDs, whose totals outgrow the IDs' own type.
+        "18446744073709551615   18446744073709551615\n".repeat(1 << 12),
+        // A valid equation far longer than any real one, which can't be solved.
+        format!("239:{}", " 7".repeat(34)),
+    ]
+}
+
+/// Variations on a valid input, each broken in a different way.
+fn mutations(input: &str, rng: &mut Rng) -> Vec<String> {
+    let boundaries = input
+        .char_indices()
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let lines = input.lines().collect::<Vec<_>>();
+    if boundaries.is_empty() || lines.is_empty() {
+        return Vec::new();
+    }
+
+    let mut mutations = Vec::new();
+    for _ in 0..4 {
+        let cut = *rng.choose(&boundaries);
+        mutations.push(input[..cut].to_string());
+
+        let mut broken = input.to_string();
+        let symbol = *rng.choose(&['é', '🎄', '\0', '^', '#', '.', '|', ',', ':', '-', '9', ' ']);
+        broken.insert(*rng.choose(&boundaries), symbol);
+        mutations.push(broken);
+
+        let line = rng.below(lines.len());
+        let mut removed = lines.clone();
+        removed.remove(line);
+        mutations.push(removed.join("\n"));
+
+        let mut ragged = lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        ragged[line].push_str(lines[line]);
+        mutations.push(ragged.join("\n"));
+        ragged[line].clear();
+        mutations.push(ragged.join("\n"));
+
+        // Still well formed, but contradicting the rest, such as a day 5 rule turned
+        // around, alone and alongside the original.
+        let pairs = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let (left, right) = line.split_once(['|', ',', ':', ' '])?;
+                let separator = &line[left.len()..left.len() + 1];
+                Some((index, format!("{}{}{}", right, separator, left)))
+            })
+            .collect::<Vec<_>>();
+        if !pairs.is_empty() {
+            let (line, swapped) = rng.choose(&pairs).clone();
+            let mut turned = lines.clone();
+            turned[line] = &swapped;
+            mutations.push(turned.join("\n"));
+            turned.insert(line, lines[line]);
+            mutations.push(turned.join("\n"));
+        }
+    }
+    mutations.push(lines.join("\r\n"));
+    mutations.push(input.repeat(4));
+    mutations
+}
+
+/// Everything to throw at `day`: the oddities, huge inputs, and mutations of its examples,
+/// the start of its real input and a generated one. Only the start of the real input is
+/// used so the slower days don't take their full time for every mutation.
+fn inputs(day: &Day, rng: &mut Rng) -> Vec<String> {
+    let mut inputs = ODDITIES
+        .iter()
+        .map(|input| input.to_string())
+        .collect::<Vec<_>>();
+    inputs.extend(huge());
+
+    let excerpt = parser::load_input(day.day)
+        .ok()
+        .map(|input| input.lines().take(20).collect::<Vec<_>>().join("\n"));
+    let examples = Example::load(parser::YEAR, day.day)
+        .unwrap()
+        .into_iter()
+        .filter(|example| !example.is_placeholder())
+        .map(|example| example.input);
+    let valid = [excerpt, (day.generate)(rng, 20)];
+    for input in valid.into_iter().flatten().chain(examples) {
+        inputs.extend(mutations(&input, rng));
+        inputs.push(input);
+    }
+    inputs
+}
+
+/// Run `f` on `input` on its own thread, describing how it failed if it panicked or took
+/// too long.
+fn survives(
+    day: usize,
+    what: &str,
+    input: &str,
+    f: impl FnOnce(&str) + Send + 'static,
+) -> Result<(), String> {
+    let (sender, receiver) = mpsc::channel();
+    let owned = input.to_string();
+    thread::spawn(move || {
+        f(&owned);
+        sender.send(()).ok();
+    });
+
+    let failure = match receiver.recv_timeout(TIMEOUT) {
+        Ok(()) => return Ok(()),
+        Err(mpsc::RecvTimeoutError::Disconnected) => "panicked",
+        Err(mpsc::RecvTimeoutError::Timeout) => "hung",
+    };
+    let excerpt = input.chars().take(100).collect::<String>();
+    Err(format!("day {} {} {} on {:?}", day, what, failure, excerpt))
+}
+
+#[test]
+fn every_day_survives_malformed_input() {
+    let mut failures = Vec::new();
+    for day in registry::days() {
+        let mut rng = Rng::new(day.day as u64);
+        for input in inputs(&day, &mut rng) {
+            for implementations in &day.parts {
+                for implementation in implementations {
+                    let run = implementation.run;
+                    failures.extend(
+                        survives(day.day, implementation.name, &input, move |input| {
+                            run(input).ok();
+                        })
+                        .err(),
+                    );
+                }
+            }
+
+            let render = day.render;
+            failures.extend(
+                survives(day.day, "render", &input, move |input| {
+                    render(input).ok();
+                })
+                .err(),
+            );
+            let animate = day.animate;
+            failures.extend(
+                survives(day.day, "animate", &input, move |input| {
+                    animate(input, &mut Recording::new(1, 10)).ok();
+                })
+                .err(),
+            );
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,256 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use strum_macros::EnumIter;
+
+/// A `(row, col)` position, with rows increasing southwards and columns eastwards.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point {
+    pub row: isize,
+    pub col: isize,
+}
+
+impl Point {
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    pub fn manhattan(&self, other: &Point) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    /// Whether the point lies within a `height` by `width` area anchored at the origin.
+    pub fn in_bounds(&self, height: usize, width: usize) -> bool {
+        self.row >= 0
+            && self.col >= 0
+            && (self.row as usize) < height
+            && (self.col as usize) < width
+    }
+
+    /// The point one step away in `direction`.
+    pub fn step(self, direction: impl Into<Point>) -> Point {
+        self + direction.into()
+    }
+
+    /// The point `distance` steps away in `direction`.
+    pub fn offset(self, direction: impl Into<Point>, distance: isize) -> Point {
+        self + direction.into() * distance
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: isize) -> Point {
+        Point::new(self.row * scale, self.col * scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.row, -self.col)
+    }
+}
+
+/// The four compass directions, in clockwise order from north.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
+pub enum Direction4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction4 {
+    pub fn turn_right(&self) -> Direction4 {
+        match self {
+            Direction4::North => Direction4::East,
+            Direction4::East => Direction4::South,
+            Direction4::South => Direction4::West,
+            Direction4::West => Direction4::North,
+        }
+    }
+
+    pub fn turn_left(&self) -> Direction4 {
+        self.reverse().turn_right()
+    }
+
+    pub fn reverse(&self) -> Direction4 {
+        self.turn_right().turn_right()
+    }
+}
+
+impl From<Direction4> for Point {
+    fn from(direction: Direction4) -> Point {
+        match direction {
+            Direction4::North => Point::new(-1, 0),
+            Direction4::East => Point::new(0, 1),
+            Direction4::South => Point::new(1, 0),
+            Direction4::West => Point::new(0, -1),
+        }
+    }
+}
+
+/// The four compass directions and the four diagonals between them, in clockwise order
+/// from north.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    fn rotate(&self, eighths: usize) -> Direction8 {
+        Self::ALL[(*self as usize + eighths) % Self::ALL.len()]
+    }
+
+    /// Turn 45 degrees clockwise.
+    pub fn turn_right(&self) -> Direction8 {
+        self.rotate(1)
+    }
+
+    /// Turn 45 degrees anticlockwise.
+    pub fn turn_left(&self) -> Direction8 {
+        self.rotate(7)
+    }
+
+    pub fn reverse(&self) -> Direction8 {
+        self.rotate(4)
+    }
+
+    pub fn diagonal(&self) -> bool {
+        matches!(
+            self,
+            Direction8::NorthEast
+                | Direction8::SouthEast
+                | Direction8::SouthWest
+                | Direction8::NorthWest
+        )
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Direction8 {
+        match direction {
+            Direction4::North => Direction8::North,
+            Direction4::East => Direction8::East,
+            Direction4::South => Direction8::South,
+            Direction4::West => Direction8::West,
+        }
+    }
+}
+
+impl From<Direction8> for Point {
+    fn from(direction: Direction8) -> Point {
+        match direction {
+            Direction8::North => Point::new(-1, 0),
+            Direction8::NorthEast => Point::new(-1, 1),
+            Direction8::East => Point::new(0, 1),
+            Direction8::SouthEast => Point::new(1, 1),
+            Direction8::South => Point::new(1, 0),
+            Direction8::SouthWest => Point::new(1, -1),
+            Direction8::West => Point::new(0, -1),
+            Direction8::NorthWest => Point::new(-1, -1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+        assert_eq!(a + b, Point::new(5, 0));
+        assert_eq!(a - b, Point::new(-3, 4));
+        assert_eq!(a * 3, Point::new(3, 6));
+        assert_eq!(-a, Point::new(-1, -2));
+        assert_eq!(a.manhattan(&b), 7);
+    }
+
+    #[test]
+    fn bounds() {
+        assert!(Point::new(0, 0).in_bounds(2, 3));
+        assert!(Point::new(1, 2).in_bounds(2, 3));
+        assert!(!Point::new(2, 0).in_bounds(2, 3));
+        assert!(!Point::new(0, -1).in_bounds(2, 3));
+    }
+
+    #[test]
+    fn stepping() {
+        let origin = Point::default();
+        assert_eq!(origin.step(Direction4::North), Point::new(-1, 0));
+        assert_eq!(origin.offset(Direction8::SouthWest, 3), Point::new(3, -3));
+    }
+
+    #[test]
+    fn turning() {
+        assert_eq!(Direction4::North.turn_right(), Direction4::East);
+        assert_eq!(Direction4::North.turn_left(), Direction4::West);
+        assert_eq!(Direction4::East.reverse(), Direction4::West);
+        assert_eq!(Direction8::NorthWest.turn_right(), Direction8::North);
+        assert_eq!(Direction8::North.turn_left(), Direction8::NorthWest);
+        assert_eq!(Direction8::NorthEast.reverse(), Direction8::SouthWest);
+    }
+
+    #[test]
+    fn iteration() {
+        assert_eq!(Direction4::iter().count(), 4);
+        assert_eq!(Direction8::iter().filter(|d| d.diagonal()).count(), 4);
+        for direction in Direction8::iter() {
+            assert_eq!(
+                Point::from(direction) + Point::from(direction.reverse()),
+                Point::default()
+            );
+        }
+    }
+}
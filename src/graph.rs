@@ -0,0 +1,630 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt,
+    hash::Hash,
+};
+
+/// What a search should do after visiting a node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Control {
+    /// Go on to the node's neighbours.
+    Continue,
+    /// Don't go on to the node's neighbours, but carry on searching elsewhere.
+    Skip,
+    /// End the search.
+    Stop,
+}
+
+/// Breadth first search from `starts`, calling `visit` with each node and its distance in
+/// steps from the nearest start, nearest first. Returns every node visited.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut visit: impl FnMut(&N, usize) -> Control,
+) -> HashSet<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if visited.insert(start.clone()) {
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, depth)) = queue.pop_front() {
+        match visit(&node, depth) {
+            Control::Continue => (),
+            Control::Skip => continue,
+            Control::Stop => break,
+        }
+
+        for next in neighbours(&node) {
+            if visited.insert(next.clone()) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+
+    visited
+}
+
+/// Depth first search from `start`, calling `visit` with each node, in preorder, and its
+/// depth in the search tree. Returns every node visited.
+pub fn dfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut visit: impl FnMut(&N, usize) -> Control,
+) -> HashSet<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::new();
+    let mut stack = vec![(start, 0)];
+
+    while let Some((node, depth)) = stack.pop() {
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+
+        match visit(&node, depth) {
+            Control::Continue => (),
+            Control::Skip => continue,
+            Control::Stop => break,
+        }
+
+        // Push in reverse so neighbours are visited in the order they're given.
+        let next = neighbours(&node)
+            .into_iter()
+            .filter(|next| !visited.contains(next))
+            .collect::<Vec<_>>();
+        stack.extend(next.into_iter().rev().map(|next| (next, depth + 1)));
+    }
+
+    visited
+}
+
+/// A set of nodes which depend on each other in a loop, each depending on the next and the
+/// last on the first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: fmt::Debug> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cycle through {:?}", self.0)
+    }
+}
+
+impl<N: fmt::Debug> Error for Cycle<N> {}
+
+/// The number of distinct paths from `start` to a node satisfying `goal`, which ends a
+/// path. Counts are memoised, so shared sub-paths are only explored once.
+pub fn count_paths<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Result<usize, Cycle<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut counts = HashMap::new();
+    // Nodes still waiting on their neighbours' counts, which form the current path.
+    let mut expanding = HashSet::new();
+    let mut stack = vec![(start.clone(), false)];
+
+    while let Some((node, expanded)) = stack.pop() {
+        if counts.contains_key(&node) {
+            continue;
+        }
+        if goal(&node) {
+            counts.insert(node, 1);
+            continue;
+        }
+
+        if expanded {
+            let count = neighbours(&node)
+                .into_iter()
+                .map(|next| counts[&next])
+                .sum();
+            expanding.remove(&node);
+            counts.insert(node, count);
+            continue;
+        }
+
+        expanding.insert(node.clone());
+        stack.push((node.clone(), true));
+        for next in neighbours(&node) {
+            if expanding.contains(&next) {
+                let path = stack
+                    .iter()
+                    .filter(|(_, expanded)| *expanded)
+                    .map(|(node, _)| node.clone())
+                    .skip_while(|node| *node != next)
+                    .collect();
+                return Err(Cycle(path));
+            }
+            if !counts.contains_key(&next) {
+                stack.push((next, false));
+            }
+        }
+    }
+
+    Ok(counts[&start])
+}
+
+/// The cheapest route found by a shortest path search.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path<N> {
+    pub cost: usize,
+    /// Every node along the route, from the start to the goal inclusive.
+    pub nodes: Vec<N>,
+}
+
+/// Nodes indexed in the order they're discovered, so searches can refer to them cheaply.
+struct Discovered<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+}
+
+impl<N: Clone + Eq + Hash> Discovered<N> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// The node's index, and whether it's newly discovered.
+    fn insert(&mut self, node: N) -> (usize, bool) {
+        match self.index.get(&node) {
+            Some(&index) => (index, false),
+            None => {
+                self.index.insert(node.clone(), self.nodes.len());
+                self.nodes.push(node);
+                (self.nodes.len() - 1, true)
+            }
+        }
+    }
+}
+
+/// A* search for the cheapest path from `start` to a node satisfying `goal`, where
+/// `neighbours` gives each neighbour with the cost of moving to it.
+///
+/// `heuristic` estimates the remaining cost from a node to the nearest goal. The path found
+/// is only guaranteed to be cheapest if it never overestimates.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut discovered = Discovered::new();
+    let mut costs = vec![0];
+    let mut parents: Vec<Option<usize>> = vec![None];
+    let mut heap = BinaryHeap::new();
+
+    // Take the lowest estimate first, breaking ties with the furthest along, so a search
+    // with an exact heuristic heads straight for the goal.
+    discovered.insert(start.clone());
+    heap.push((Reverse(heuristic(&start)), 0, Reverse(0)));
+
+    while let Some((_, cost, Reverse(index))) = heap.pop() {
+        if cost > costs[index] {
+            // A cheaper route to this node has been found since this entry was queued.
+            continue;
+        }
+
+        let node = discovered.nodes[index].clone();
+        if goal(&node) {
+            let mut nodes = vec![node];
+            let mut current = index;
+            while let Some(parent) = parents[current] {
+                nodes.push(discovered.nodes[parent].clone());
+                current = parent;
+            }
+            nodes.reverse();
+            return Some(Path { cost, nodes });
+        }
+
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            let estimate = next_cost + heuristic(&next);
+            let (next, new) = discovered.insert(next);
+            if new {
+                costs.push(next_cost);
+                parents.push(Some(index));
+            } else if next_cost < costs[next] {
+                costs[next] = next_cost;
+                parents[next] = Some(index);
+            } else {
+                continue;
+            }
+            heap.push((Reverse(estimate), next_cost, Reverse(next)));
+        }
+    }
+
+    None
+}
+
+/// Dijkstra's search for the cheapest path from `start` to a node satisfying `goal`.
+pub fn shortest_path<N, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    astar(start, neighbours, |_| 0, goal)
+}
+
+/// The cost of the cheapest path from `start` to every node reachable from it.
+pub fn dijkstra<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut costs = HashMap::from([(start.clone(), 0)]);
+    let mut discovered = Discovered::new();
+    let mut heap = BinaryHeap::from([Reverse((0, discovered.insert(start).0))]);
+
+    while let Some(Reverse((cost, index))) = heap.pop() {
+        let node = discovered.nodes[index].clone();
+        if cost > costs[&node] {
+            continue;
+        }
+
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|known| next_cost < *known) {
+                costs.insert(next.clone(), next_cost);
+                heap.push(Reverse((next_cost, discovered.insert(next).0)));
+            }
+        }
+    }
+
+    costs
+}
+
+/// Order `nodes` so that each comes before its `successors`, keeping the given order where
+/// the edges allow. Successors which aren't in `nodes` are ignored.
+pub fn toposort<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+) -> Result<Vec<N>, Cycle<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut discovered = Discovered::new();
+    for node in nodes {
+        discovered.insert(node);
+    }
+
+    let count = discovered.nodes.len();
+    let mut edges = vec![Vec::new(); count];
+    let mut predecessors = vec![Vec::new(); count];
+    for (index, node) in discovered.nodes.iter().enumerate() {
+        for next in successors(node) {
+            if let Some(&next) = discovered.index.get(&next) {
+                edges[index].push(next);
+                predecessors[next].push(index);
+            }
+        }
+    }
+
+    let mut in_degree = predecessors.iter().map(Vec::len).collect::<Vec<_>>();
+    // Always take the earliest ready node, so ties keep their given order.
+    let mut ready = (0..count)
+        .filter(|index| in_degree[*index] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(Reverse(index)) = ready.pop() {
+        order.push(index);
+        for &next in &edges[index] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+
+    if order.len() < count {
+        // Every unsorted node still has an unsorted predecessor, so walking back through
+        // them must eventually repeat a node.
+        let mut seen = HashMap::new();
+        let mut current = (0..count)
+            .find(|index| in_degree[*index] > 0)
+            .expect("Should have an unsorted node");
+        let mut walk = Vec::new();
+        while !seen.contains_key(&current) {
+            seen.insert(current, walk.len());
+            walk.push(current);
+            current = *predecessors[current]
+                .iter()
+                .find(|previous| in_degree[**previous] > 0)
+                .expect("Unsorted node should have an unsorted predecessor");
+        }
+
+        // The walk went backwards, so reverse it and start from where it repeated.
+        let mut cycle = walk[seen[&current]..].to_vec();
+        cycle.reverse();
+        cycle.rotate_right(1);
+        return Err(Cycle(
+            cycle
+                .into_iter()
+                .map(|index| discovered.nodes[index].clone())
+                .collect(),
+        ));
+    }
+
+    Ok(order
+        .into_iter()
+        .map(|index| discovered.nodes[index].clone())
+        .collect())
+}
+
+/// Disjoint-set union, tracking which nodes have been joined into the same set.
+pub struct DisjointSet<N> {
+    discovered: Discovered<N>,
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl<N: Clone + Eq + Hash> Default for DisjointSet<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Clone + Eq + Hash> DisjointSet<N> {
+    pub fn new() -> Self {
+        Self {
+            discovered: Discovered::new(),
+            parents: Vec::new(),
+            sizes: Vec::new(),
+        }
+    }
+
+    /// Add `node` in a set of its own, if it isn't already in one.
+    pub fn insert(&mut self, node: N) -> usize {
+        let (index, new) = self.discovered.insert(node);
+        if new {
+            self.parents.push(index);
+            self.sizes.push(1);
+        }
+        index
+    }
+
+    fn root(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            // Path halving keeps later lookups short.
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    /// Join the sets holding `a` and `b`, adding either if needed. Returns whether they
+    /// were in different sets.
+    pub fn union(&mut self, a: N, b: N) -> bool {
+        let a = self.insert(a);
+        let b = self.insert(b);
+        let (mut a, mut b) = (self.root(a), self.root(b));
+        if a == b {
+            return false;
+        }
+
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        true
+    }
+
+    /// Whether `a` and `b` are in the same set.
+    pub fn connected(&mut self, a: &N, b: &N) -> bool {
+        match (self.discovered.index.get(a), self.discovered.index.get(b)) {
+            (Some(&a), Some(&b)) => self.root(a) == self.root(b),
+            _ => a == b,
+        }
+    }
+
+    /// The size of the set holding `node`, or zero if it hasn't been added.
+    pub fn size_of(&mut self, node: &N) -> usize {
+        match self.discovered.index.get(node) {
+            Some(&index) => {
+                let root = self.root(index);
+                self.sizes[root]
+            }
+            None => 0,
+        }
+    }
+
+    /// Every set, each in the order its nodes were added, ordered by their first node.
+    pub fn sets(&mut self) -> Vec<Vec<N>> {
+        let mut sets: Vec<Vec<N>> = Vec::new();
+        let mut positions = HashMap::new();
+        for index in 0..self.parents.len() {
+            let root = self.root(index);
+            let position = *positions.entry(root).or_insert_with(|| {
+                sets.push(Vec::new());
+                sets.len() - 1
+            });
+            sets[position].push(self.discovered.nodes[index].clone());
+        }
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edges of a small directed graph: 1 → 2 → 4, 1 → 3 → 4 → 5.
+    fn successors(node: &u32) -> Vec<u32> {
+        match node {
+            1 => vec![2, 3],
+            2 | 3 => vec![4],
+            4 => vec![5],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn breadth_first() {
+        let mut order = Vec::new();
+        let visited = bfs([1], successors, |node, depth| {
+            order.push((*node, depth));
+            Control::Continue
+        });
+        assert_eq!(order, vec![(1, 0), (2, 1), (3, 1), (4, 2), (5, 3)]);
+        assert_eq!(visited.len(), 5);
+
+        let mut order = Vec::new();
+        bfs([1], successors, |node, _| {
+            order.push(*node);
+            match node {
+                2 => Control::Stop,
+                _ => Control::Continue,
+            }
+        });
+        assert_eq!(order, vec![1, 2]);
+    }
+
+    #[test]
+    fn depth_first() {
+        let mut order = Vec::new();
+        dfs(1, successors, |node, depth| {
+            order.push((*node, depth));
+            Control::Continue
+        });
+        assert_eq!(order, vec![(1, 0), (2, 1), (4, 2), (5, 3), (3, 1)]);
+
+        let visited = dfs(1, successors, |node, _| match node {
+            4 => Control::Skip,
+            _ => Control::Continue,
+        });
+        assert!(!visited.contains(&5));
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(count_paths(1, successors, |node| *node == 5), Ok(2));
+        assert_eq!(count_paths(3, successors, |node| *node == 2), Ok(0));
+
+        let looped = |node: &u32| match node {
+            4 => vec![2],
+            _ => successors(node),
+        };
+        assert_eq!(
+            count_paths(1, looped, |node| *node == 5),
+            Err(Cycle(vec![4, 2]))
+        );
+    }
+
+    #[test]
+    fn weighted() {
+        // 1 → 2 costs 1 but 2 → 4 costs 10, so it's cheaper to go via 3.
+        let weighted = |node: &u32| match node {
+            1 => vec![(2, 1), (3, 4)],
+            2 => vec![(4, 10)],
+            3 => vec![(4, 1)],
+            _ => vec![],
+        };
+
+        assert_eq!(
+            shortest_path(1, weighted, |node| *node == 4),
+            Some(Path {
+                cost: 5,
+                nodes: vec![1, 3, 4]
+            })
+        );
+        assert_eq!(shortest_path(2, weighted, |node| *node == 3), None);
+        assert_eq!(
+            dijkstra(1, weighted),
+            HashMap::from([(1, 0), (2, 1), (3, 4), (4, 5)])
+        );
+    }
+
+    #[test]
+    fn astar_on_a_grid() {
+        // An open 10x10 grid, where the manhattan distance is an exact heuristic.
+        let neighbours = |&(row, col): &(i32, i32)| {
+            [
+                (row - 1, col),
+                (row + 1, col),
+                (row, col - 1),
+                (row, col + 1),
+            ]
+            .into_iter()
+            .filter(|(row, col)| (0..10).contains(row) && (0..10).contains(col))
+            .map(|next| (next, 1))
+            .collect::<Vec<_>>()
+        };
+        let mut expanded = 0;
+        let path = astar(
+            (0, 0),
+            |node| {
+                expanded += 1;
+                neighbours(node)
+            },
+            |(row, col)| (9 - row + 9 - col) as usize,
+            |node| *node == (9, 9),
+        )
+        .unwrap();
+
+        assert_eq!(path.cost, 18);
+        assert_eq!(path.nodes.len(), 19);
+        assert!(expanded < 50);
+    }
+
+    #[test]
+    fn topological_order() {
+        assert_eq!(
+            toposort([5, 4, 3, 2, 1], successors),
+            Ok(vec![1, 3, 2, 4, 5])
+        );
+        // Successors outside the given nodes are ignored.
+        assert_eq!(toposort([4, 2], successors), Ok(vec![2, 4]));
+
+        let looped = |node: &u32| match node {
+            5 => vec![3],
+            _ => successors(node),
+        };
+        assert_eq!(toposort([1, 2, 3, 4, 5], looped), Err(Cycle(vec![3, 4, 5])));
+        assert_eq!(Cycle(vec![3, 4, 5]).to_string(), "cycle through [3, 4, 5]");
+    }
+
+    #[test]
+    fn disjoint_sets() {
+        let mut sets = DisjointSet::new();
+        assert!(sets.union('a', 'b'));
+        assert!(sets.union('c', 'd'));
+        assert!(!sets.union('b', 'a'));
+        sets.insert('e');
+
+        assert!(sets.connected(&'a', &'b'));
+        assert!(!sets.connected(&'a', &'c'));
+        assert_eq!(sets.size_of(&'c'), 2);
+        assert_eq!(sets.size_of(&'z'), 0);
+
+        assert!(sets.union('d', 'b'));
+        assert_eq!(sets.size_of(&'a'), 4);
+        assert_eq!(sets.sets(), vec![vec!['a', 'b', 'c', 'd'], vec!['e']]);
+    }
+}
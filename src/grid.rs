@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    geometry::{Direction4, Direction8, Point},
+    parser::{ParseError, Source},
+};
+
+/// A dense, row-major grid of cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "Grid cells should match its dimensions"
+        );
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Build a grid from text, one row per line, converting each char with `f`.
+    ///
+    /// Leading and trailing whitespace is trimmed from the input and from each line so
+    /// indented example strings can be parsed directly. Chars that `f` rejects and rows of
+    /// differing widths are reported as errors.
+    pub fn parse(source: &Source, f: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        Self::parse_marked(source, |_| false, f).map(|(grid, _)| grid)
+    }
+
+    /// Build a grid as [`parse`](Grid::parse) does, also recording where each char that
+    /// `marker` accepts appears. Markers are still converted with `f` like any other char.
+    pub fn parse_marked<'a>(
+        source: &Source<'a>,
+        marker: impl Fn(char) -> bool,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<(Self, Markers<'a>), ParseError> {
+        let mut cells = Vec::new();
+        let mut markers = Markers::default();
+        let mut width = 0;
+        let mut height = 0;
+
+        for line in source.lines() {
+            let before = cells.len();
+            for (i, c) in line.char_indices() {
+                let token = &line[i..i + c.len_utf8()];
+                if marker(c) {
+                    let point = Point::new(height as isize, (cells.len() - before) as isize);
+                    markers.found.entry(c).or_default().push((point, token));
+                }
+                match f(c) {
+                    Some(cell) => cells.push(cell),
+                    None => return Err(source.error(token, "unexpected character")),
+                }
+            }
+
+            let row_width = cells.len() - before;
+            if height == 0 {
+                width = row_width;
+            } else if row_width != width {
+                return Err(source.error(line, format!("expected a row of width {}", width)));
+            }
+            height += 1;
+        }
+
+        Ok((Self::new(width, height, cells), markers))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.in_bounds(self.height, self.width)
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        self.contains(point)
+            .then(|| point.row as usize * self.width + point.col as usize)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.cells[index])
+    }
+
+    /// Every point in the grid, in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len())
+            .map(move |index| Point::new((index / width) as isize, (index % width) as isize))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The in-bounds orthogonal neighbours of `point`, clockwise from north.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction4::iter()
+            .map(move |direction| point.step(direction))
+            .filter(|next| self.contains(*next))
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbours of `point`, clockwise from north.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction8::iter()
+            .map(move |direction| point.step(direction))
+            .filter(|next| self.contains(*next))
+    }
+
+    /// A grid of the same shape with every cell converted by `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero chunk size, which an empty grid would give us.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "Column should be within the grid");
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    /// The first point, in row-major order, holding `value`.
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .find(|(_, cell)| *cell == value)
+            .map(|(point, _)| point)
+    }
+
+    /// Every point holding `value`, in row-major order.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(point, _)| point)
+    }
+}
+
+/// Where marker chars appeared in a parsed grid, each in row-major order.
+#[derive(Debug, Default)]
+pub struct Markers<'a> {
+    found: HashMap<char, Vec<(Point, &'a str)>>,
+}
+
+impl<'a> Markers<'a> {
+    /// Every point at which `marker` appeared.
+    pub fn all(&self, marker: char) -> Vec<Point> {
+        self.found.get(&marker).map_or(Vec::new(), |found| {
+            found.iter().map(|(point, _)| *point).collect()
+        })
+    }
+
+    /// The only point at which `marker` appeared, describing it as `what` if it's missing.
+    /// `source` must be the one the grid was parsed from.
+    pub fn unique(
+        &self,
+        source: &Source<'a>,
+        marker: char,
+        what: &str,
+    ) -> Result<Point, ParseError> {
+        match self.found.get(&marker).map(Vec::as_slice) {
+            Some([(point, _)]) => Ok(*point),
+            Some([_, (_, duplicate), ..]) => {
+                Err(source.error(duplicate, format!("expected only one {:?}", marker)))
+            }
+            _ => Err(source.error(source.end(), format!("expected {}", what))),
+        }
+    }
+
+    /// The points at which each marker appeared.
+    pub fn into_groups(self) -> HashMap<char, Vec<Point>> {
+        self.found
+            .into_iter()
+            .map(|(marker, found)| (marker, found.into_iter().map(|(point, _)| point).collect()))
+            .collect()
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point).expect("Point should be within the grid")
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .expect("Point should be within the grid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST: &str = "
+        abc
+        def
+    ";
+
+    #[test]
+    fn parse_and_get() {
+        let grid = Grid::parse(&Source::new(0, TEST), Some).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.get(Point::new(1, 2)), Some(&'f'));
+        assert_eq!(grid.get(Point::new(2, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid[Point::new(0, 1)], 'b');
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::parse(&Source::new(0, TEST), Some).unwrap();
+        assert_eq!(
+            grid.neighbours4(Point::new(0, 0)).collect::<Vec<_>>(),
+            vec![Point::new(0, 1), Point::new(1, 0)]
+        );
+        assert_eq!(
+            grid.neighbours8(Point::new(0, 1)).collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 2),
+                Point::new(1, 2),
+                Point::new(1, 1),
+                Point::new(1, 0),
+                Point::new(0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::parse(&Source::new(0, TEST), Some).unwrap();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(
+            grid.columns()
+                .map(|col| col.collect::<String>())
+                .collect::<Vec<_>>(),
+            vec!["ad", "be", "cf"]
+        );
+    }
+
+    #[test]
+    fn find() {
+        let grid = Grid::parse(&Source::new(0, "aba\nbab"), Some).unwrap();
+        assert_eq!(grid.find(&'b'), Some(Point::new(0, 1)));
+        assert_eq!(grid.find(&'z'), None);
+        assert_eq!(
+            grid.find_all(&'a').collect::<Vec<_>>(),
+            vec![Point::new(0, 0), Point::new(0, 2), Point::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn markers() {
+        let source = Source::new(0, "a.b\n.^a");
+        let (grid, markers) = Grid::parse_marked(&source, |c| c != '.', Some).unwrap();
+        assert_eq!(grid[Point::new(1, 1)], '^');
+        assert_eq!(markers.all('a'), vec![Point::new(0, 0), Point::new(1, 2)]);
+        assert_eq!(markers.all('z'), vec![]);
+        assert_eq!(
+            markers.unique(&source, '^', "a start"),
+            Ok(Point::new(1, 1))
+        );
+
+        let error = markers.unique(&source, 'a', "an a").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.reason, "expected only one 'a'");
+        let error = markers.unique(&source, 'z', "a z").unwrap_err();
+        assert_eq!((error.line, error.reason.as_str()), (2, "expected a z"));
+
+        let groups = markers.into_groups();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&'b'], vec![Point::new(0, 2)]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Grid::parse(&Source::new(0, "ab\nc"), Some).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+
+        let error = Grid::parse(&Source::new(0, "12\n3x"), |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "x"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    answers::{Answer, AnswersError},
+    parser,
+};
+
+/// How the site judged a guess.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying which way.
+    Wrong,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Guess {
+    pub answer: Answer,
+    pub verdict: Verdict,
+}
+
+/// Every answer submitted for a year and how it was judged, keyed by `dayN` then `partN`,
+/// so no guess is ever sent twice.
+#[derive(Debug, Default, PartialEq)]
+pub struct Guesses {
+    days: BTreeMap<String, BTreeMap<String, Vec<Guess>>>,
+}
+
+impl Guesses {
+    pub fn path(year: usize) -> PathBuf {
+        parser::input_dir()
+            .join(year.to_string())
+            .join("guesses.toml")
+    }
+
+    /// Load a year's guesses, treating a missing file as none.
+    pub fn load(year: usize) -> Result<Self, AnswersError> {
+        match fs::read_to_string(Self::path(year)) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(AnswersError::Io(error)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        toml::from_str(text)
+            .map(|days| Self { days })
+            .map_err(AnswersError::Malformed)
+    }
+
+    pub fn save(&self, year: usize) -> Result<(), AnswersError> {
+        let text = toml::to_string(&self.days).map_err(AnswersError::Unwritable)?;
+        fs::write(Self::path(year), text).map_err(AnswersError::Io)
+    }
+
+    /// The guesses made for a part, oldest first.
+    pub fn get(&self, day: usize, part: usize) -> &[Guess] {
+        self.days
+            .get(&format!("day{}", day))
+            .and_then(|parts| parts.get(&format!("part{}", part)))
+            .map_or(&[], |guesses| guesses.as_slice())
+    }
+
+    pub fn record(&mut self, day: usize, part: usize, guess: Guess) {
+        self.days
+            .entry(format!("day{}", day))
+            .or_default()
+            .entry(format!("part{}", part))
+            .or_default()
+            .push(guess);
+    }
+
+    /// How `answer` would be judged, if earlier guesses already show it: the same verdict
+    /// as before for a repeated guess, wrong once the correct answer is known, and too high
+    /// or too low for a number beyond one that already was.
+    pub fn judge(&self, day: usize, part: usize, answer: &Answer) -> Option<Verdict> {
+        let guesses = self.get(day, part);
+        // The same number is a repeat however it's stored, as is the same text.
+        let repeats = |guess: &&Guess| match (guess.answer.number(), answer.number()) {
+            (Some(guessed), Some(value)) => guessed == value,
+            _ => guess.answer.to_string() == answer.to_string(),
+        };
+        if let Some(guess) = guesses.iter().find(repeats) {
+            return Some(guess.verdict);
+        }
+        if guesses
+            .iter()
+            .any(|guess| guess.verdict == Verdict::Correct)
+        {
+            return Some(Verdict::Wrong);
+        }
+
+        let value = answer.number()?;
+        let beyond = |verdict, beyond: fn(i128, i128) -> bool| {
+            guesses.iter().any(|guess| {
+                guess.verdict == verdict && guess.answer.number().is_some_and(|n| beyond(value, n))
+            })
+        };
+        if beyond(Verdict::TooHigh, |value, high| value >= high) {
+            Some(Verdict::TooHigh)
+        } else if beyond(Verdict::TooLow, |value, low| value <= low) {
+            Some(Verdict::TooLow)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guess(answer: impl Into<Answer>, verdict: Verdict) -> Guess {
+        Guess {
+            answer: answer.into(),
+            verdict,
+        }
+    }
+
+    #[test]
+    fn judge() {
+        let mut guesses = Guesses::default();
+        assert_eq!(guesses.judge(1, 1, &50.into()), None);
+
+        guesses.record(1, 1, guess(100, Verdict::TooHigh));
+        guesses.record(1, 1, guess(10, Verdict::TooLow));
+        guesses.record(1, 1, guess("abc", Verdict::Wrong));
+        guesses.record(1, 1, guess(42, Verdict::Wrong));
+        assert_eq!(guesses.judge(1, 1, &100.into()), Some(Verdict::TooHigh));
+        assert_eq!(
+            guesses.judge(1, 1, &Answer::Big(1 << 80)),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(guesses.judge(1, 1, &(-5).into()), Some(Verdict::TooLow));
+        assert_eq!(guesses.judge(1, 1, &"abc".into()), Some(Verdict::Wrong));
+        assert_eq!(guesses.judge(1, 1, &42.into()), Some(Verdict::Wrong));
+        assert_eq!(guesses.judge(1, 1, &Answer::Big(42)), Some(Verdict::Wrong));
+        assert_eq!(
+            guesses.judge(1, 1, &Answer::Text("42".to_string())),
+            Some(Verdict::Wrong)
+        );
+        assert_eq!(guesses.judge(1, 1, &"abd".into()), None);
+        assert_eq!(guesses.judge(1, 1, &50.into()), None);
+        assert_eq!(guesses.judge(1, 2, &100.into()), None);
+
+        guesses.record(1, 1, guess(50, Verdict::Correct));
+        assert_eq!(guesses.judge(1, 1, &50.into()), Some(Verdict::Correct));
+        assert_eq!(guesses.judge(1, 1, &51.into()), Some(Verdict::Wrong));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut guesses = Guesses::default();
+        guesses.record(3, 2, guess(7, Verdict::TooLow));
+        guesses.record(3, 2, guess(Answer::Big(i128::MAX), Verdict::TooHigh));
+        guesses.record(11, 1, guess("1,2", Verdict::Correct));
+
+        let text = toml::to_string(&guesses.days).unwrap();
+        assert!(text.contains("verdict = \"too-low\""));
+        assert_eq!(Guesses::parse(&text).unwrap(), guesses);
+    }
+}
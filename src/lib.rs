@@ -1,21 +1,39 @@
-extern crate aoc_runner;
-
-#[macro_use]
-extern crate aoc_runner_derive;
-
+pub mod animation;
+pub mod answers;
+pub mod crypt;
+pub mod examples;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod guesses;
+pub mod math;
 pub mod parser;
+pub mod random;
+pub mod registry;
+pub mod render;
+pub mod runner;
+pub mod scaffold;
+#[cfg(test)]
+mod server;
+pub mod site;
+mod solution;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+#[cfg(test)]
+mod fuzz;
 
-aoc_lib! { year = 2024 }
+pub use answers::Answer;
+pub(crate) use solution::Extras;
+pub use solution::{solve, Solution, SolveError};
@@ -1,7 +1,638 @@
-extern crate aoc_24_rs;
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    process,
+    time::Duration,
+};
 
-use aoc_runner_derive::aoc_main;
+use aoc_24_rs::{
+    animation::Recording,
+    answers::{Answer, Answers},
+    crypt::{self, Key},
+    guesses::{Guesses, Verdict},
+    parser::{self, InputError},
+    random::Rng,
+    registry::{self, Day},
+    render,
+    runner::{self, Status},
+    scaffold,
+    site::{Client, Fetched},
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 
-aoc_main! {lib = aoc_24_rs}
+/// Roughly how long a real input is, for scaling days whose input is missing.
+const DEFAULT_INPUT_BYTES: usize = 16 * 1024;
+
+#[derive(Parser)]
+#[command(about = "Advent of Code 2024 solutions")]
+struct Cli {
+    /// How to print results.
+    #[arg(long, value_enum, default_value_t = Format::Text, global = true)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Style {
+    Ansi,
+    Plain,
+    Ppm,
+    Png,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Reel {
+    Text,
+    Apng,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one day, or every day if none is given.
+    Run {
+        day: Option<usize>,
+        part: Option<usize>,
+        /// Read the input from this file, or `-` for stdin, instead of the input directory.
+        #[arg(long, requires = "day")]
+        input: Option<PathBuf>,
+        /// Solve the days at the same time, on separate threads.
+        #[cfg(feature = "parallel")]
+        #[arg(long)]
+        concurrent: bool,
+    },
+    /// List the registered days and their parts.
+    List,
+    /// Check answers against the answers manifest.
+    Verify { day: Option<usize> },
+    /// Time repeated runs of each part.
+    Bench {
+        day: Option<usize>,
+        part: Option<usize>,
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+        /// Time every implementation of each part, not just the default.
+        #[arg(long)]
+        compare: bool,
+    },
+    /// Time parsing and each part separately for every day, or just one.
+    Time {
+        day: Option<usize>,
+        #[arg(long, default_value_t = 1)]
+        runs: usize,
+    },
+    /// Time each part on generated inputs scaled up from the size of a real one, to show
+    /// how its runtime grows.
+    Scale {
+        day: Option<usize>,
+        part: Option<usize>,
+        /// The multiples of a real input's size to generate.
+        #[arg(long, value_delimiter = ',', default_values_t = [1, 10, 100, 1000])]
+        factors: Vec<usize>,
+        /// Skip sizes predicted to take longer than this, in milliseconds.
+        #[arg(long, default_value_t = 5000)]
+        budget_ms: u64,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 1)]
+        runs: usize,
+    },
+    /// Record the current answers for a day in the answers manifest.
+    Record { day: usize, part: Option<usize> },
+    /// Draw a day's state, for days with a grid to show.
+    Render {
+        day: usize,
+        #[arg(long, value_enum, default_value_t = Style::Ansi)]
+        style: Style,
+        /// Write to this file instead of stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// The width and height in pixels of each cell in an image.
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+        /// Read the input from this file, or `-` for stdin, instead of the input directory.
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+    /// Record a day's simulation step by step, for days with one to watch.
+    Animate {
+        day: usize,
+        #[arg(long, value_enum, default_value_t = Reel::Text)]
+        style: Reel,
+        /// Write to this file instead of stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Keep only every nth step.
+        #[arg(long, default_value_t = 1)]
+        every: usize,
+        /// The most frames to keep.
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+        /// The width and height in pixels of each cell in an image.
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+        /// How long to show each frame of an image, in milliseconds.
+        #[arg(long, default_value_t = 100)]
+        delay_ms: u64,
+        /// Read the input from this file, or `-` for stdin, instead of the input directory.
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+    /// Print a random input for a day, for days which can make them up.
+    Generate {
+        day: usize,
+        /// How big an input to make, in the day's own units, such as reports or digits.
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+        /// The same seed always gives the same input.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Download the missing inputs for one day, or every registered day, from the puzzle
+    /// site.
+    Fetch {
+        day: Option<usize>,
+        /// The site to fetch from, instead of the one in the environment or the real site.
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Submit an answer to the puzzle site, or the current one if none is given, unless
+    /// earlier guesses already show how it would be judged. Correct answers are recorded
+    /// in the answers manifest.
+    Submit {
+        day: usize,
+        part: usize,
+        answer: Option<String>,
+        /// The site to submit to, instead of the one in the environment or the real site.
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Encrypt every input in the input directory, so they can be committed publicly,
+    /// making a new key first if none is configured.
+    Encrypt,
+    /// Decrypt every encrypted input in the input directory.
+    Decrypt,
+    /// Start a new day: create its module from a template, register it, and add empty
+    /// placeholders for its input, answers and example.
+    NewDay { day: usize },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match execute(cli) {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(2);
+        }
+    }
+}
+
+/// Run the command, returning whether everything it checked was correct.
+fn execute(cli: Cli) -> Result<bool, Box<dyn Error>> {
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            #[cfg(feature = "parallel")]
+            concurrent,
+        } => {
+            let days = selected(day)?;
+            for day in &days {
+                selected_parts(day, part)?;
+            }
+            let load = |day: &Day| -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+                match &input {
+                    Some(path) => Ok(Some(read_input(path)?)),
+                    None => Ok(available_input(day.day)?),
+                }
+            };
+
+            #[cfg(feature = "parallel")]
+            let outcomes = match concurrent {
+                true => runner::run_days_concurrently(&days, part, load),
+                false => runner::run_days(&days, part, load),
+            };
+            #[cfg(not(feature = "parallel"))]
+            let outcomes = runner::run_days(&days, part, load);
+            let outcomes = outcomes.map_err(|error| error as Box<dyn Error>)?;
+
+            print(cli.format, &outcomes, |outcome| {
+                format!(
+                    "Day {} - Part {}: {} ({:?})",
+                    outcome.day, outcome.part, outcome.answer, outcome.time
+                )
+            })?;
+        }
+        Command::List => {
+            let days = registry::days()
+                .iter()
+                .map(|day| Listing {
+                    day: day.day,
+                    parts: day
+                        .parts
+                        .iter()
+                        .map(|implementations| {
+                            implementations
+                                .iter()
+                                .map(|implementation| implementation.name)
+                                .collect()
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+
+            print(cli.format, &days, |listing| {
+                let parts = listing
+                    .parts
+                    .iter()
+                    .map(|implementations| implementations.join(" | "))
+                    .collect::<Vec<_>>();
+                format!("Day {} - {}", listing.day, parts.join(", "))
+            })?;
+        }
+        Command::Verify { day } => {
+            let answers = Answers::load(parser::YEAR)?;
+            let mut verifications = Vec::new();
+            for day in selected(day)? {
+                verifications.extend(runner::verify(&day, &answers)?);
+            }
+
+            print(cli.format, &verifications, |verification| {
+                let detail = match (&verification.status, &verification.actual) {
+                    (Status::Incorrect, Some(actual)) => format!(
+                        " (expected {}, got {})",
+                        verification
+                            .expected
+                            .as_ref()
+                            .map(|expected| expected.to_string())
+                            .unwrap_or_default(),
+                        actual
+                    ),
+                    _ => String::new(),
+                };
+                format!(
+                    "Day {} - Part {}: {:?}{}",
+                    verification.day, verification.part, verification.status, detail
+                )
+            })?;
+
+            return Ok(verifications.iter().all(|verification| {
+                !matches!(verification.status, Status::Incorrect | Status::Malformed)
+            }));
+        }
+        Command::Bench {
+            day,
+            part,
+            runs,
+            compare,
+        } => {
+            let mut benchmarks = Vec::new();
+            for day in selected(day)? {
+                let Some(input) = available_input(day.day)? else {
+                    continue;
+                };
+                for part in selected_parts(&day, part)? {
+                    let implementations = day.implementations(part);
+                    let count = if compare { implementations.len() } else { 1 };
+                    for implementation in &implementations[..count] {
+                        benchmarks.push(runner::bench(&day, part, implementation, &input, runs)?);
+                    }
+                }
+            }
+
+            print(cli.format, &benchmarks, |benchmark| {
+                format!(
+                    "Day {} - Part {} - {:<16} min {:>12.2?}, mean {:>12.2?} over {} runs",
+                    benchmark.day,
+                    benchmark.part,
+                    benchmark.implementation,
+                    benchmark.min,
+                    benchmark.mean,
+                    benchmark.runs
+                )
+            })?;
+        }
+        Command::Time { day, runs } => {
+            let mut timings = Vec::new();
+            for day in selected(day)? {
+                let Some(input) = available_input(day.day)? else {
+                    continue;
+                };
+                timings.push(runner::time(&day, &input, runs)?);
+            }
+
+            match cli.format {
+                Format::Text => println!("{}", runner::timing_table(&timings)),
+                Format::Json => println!("{}", serde_json::to_string_pretty(&timings)?),
+            }
+        }
+        Command::Scale {
+            day,
+            part,
+            mut factors,
+            budget_ms,
+            seed,
+            runs,
+        } => {
+            factors.sort();
+            let mut scalings = Vec::new();
+            for day in selected(day)? {
+                let parts = selected_parts(&day, part)?;
+                // Without a real input to match, assume one of a typical size.
+                let bytes =
+                    parser::load_input(day.day).map_or(DEFAULT_INPUT_BYTES, |input| input.len());
+                let Some(base) = runner::base_size(&day, bytes, seed) else {
+                    continue;
+                };
+                for part in parts {
+                    scalings.push(runner::scale(
+                        &day,
+                        part,
+                        base,
+                        &factors,
+                        seed,
+                        runs,
+                        Duration::from_millis(budget_ms),
+                    )?);
+                }
+            }
+
+            match cli.format {
+                Format::Text => println!("{}", runner::scaling_table(&scalings)),
+                Format::Json => println!("{}", serde_json::to_string_pretty(&scalings)?),
+            }
+        }
+        Command::Record { day, part } => {
+            let registered = selected(Some(day))?.remove(0);
+            let Some(input) = available_input(day)? else {
+                return Ok(true);
+            };
+            let mut answers = Answers::load(parser::YEAR)?;
+
+            for part in selected_parts(&registered, part)? {
+                let answer = runner::run(&registered, part, &input)?.answer;
+                match answers.record(day, part, answer.clone()) {
+                    Some(previous) if previous != answer => {
+                        println!("Day {} - Part {}: {} (was {})", day, part, answer, previous)
+                    }
+                    _ => println!("Day {} - Part {}: {}", day, part, answer),
+                }
+            }
+
+            answers.save(parser::YEAR)?;
+        }
+        Command::Render {
+            day,
+            style,
+            output,
+            scale,
+            input,
+        } => {
+            let registered = selected(Some(day))?.remove(0);
+            let input = match &input {
+                Some(path) => read_input(path)?,
+                None => parser::load_input(day)?,
+            };
+            let canvas = (registered.render)(&input)?
+                .ok_or_else(|| format!("day {} has nothing to render", day))?;
+
+            let bytes = match style {
+                Style::Ansi => render::ansi(&canvas).into_bytes(),
+                Style::Plain => render::plain(&canvas).into_bytes(),
+                Style::Ppm => render::ppm(&canvas, scale),
+                Style::Png => render::png(&canvas, scale)?,
+            };
+            match output {
+                Some(path) => fs::write(path, bytes)?,
+                None => io::stdout().write_all(&bytes)?,
+            }
+        }
+        Command::Animate {
+            day,
+            style,
+            output,
+            every,
+            limit,
+            scale,
+            delay_ms,
+            input,
+        } => {
+            let registered = selected(Some(day))?.remove(0);
+            let input = match &input {
+                Some(path) => read_input(path)?,
+                None => parser::load_input(day)?,
+            };
+            let mut recording = Recording::new(every, limit);
+            if !(registered.animate)(&input, &mut recording)? {
+                return Err(format!("day {} has nothing to animate", day).into());
+            }
+
+            let bytes = match style {
+                Reel::Text => recording.text().into_bytes(),
+                Reel::Apng => recording.apng(scale, Duration::from_millis(delay_ms))?,
+            };
+            match output {
+                Some(path) => fs::write(path, bytes)?,
+                None => io::stdout().write_all(&bytes)?,
+            }
+        }
+        Command::Generate { day, size, seed } => {
+            let registered = selected(Some(day))?.remove(0);
+            let input = (registered.generate)(&mut Rng::new(seed), size)
+                .ok_or_else(|| format!("day {} has no generator", day))?;
+            println!("{}", input);
+        }
+        Command::Fetch { day, base_url } => {
+            let client = Client::from_env(base_url.as_deref())?;
+            let days = match day {
+                // Any day can be fetched, so its input is ready before it's solved.
+                Some(day) => vec![day],
+                None => registry::days().iter().map(|day| day.day).collect(),
+            };
+
+            let mut fetches = Vec::new();
+            for day in days {
+                fetches.push(Fetch {
+                    day,
+                    fetched: client.fetch(parser::YEAR, day)?,
+                });
+            }
+            print(cli.format, &fetches, |fetch| {
+                format!("Day {}: {:?}", fetch.day, fetch.fetched)
+            })?;
+        }
+        Command::Submit {
+            day,
+            part,
+            answer,
+            base_url,
+        } => {
+            let answer = match answer {
+                Some(answer) => answer.trim().parse::<Answer>()?,
+                None => {
+                    let registered = selected(Some(day))?.remove(0);
+                    selected_parts(&registered, Some(part))?;
+                    runner::run(&registered, part, &parser::load_input(day)?)?.answer
+                }
+            };
+            let client = Client::from_env(base_url.as_deref())?;
+            let mut guesses = Guesses::load(parser::YEAR)?;
+            let mut answers = Answers::load(parser::YEAR)?;
+
+            let submission =
+                client.submit(parser::YEAR, day, part, answer, &mut guesses, &mut answers)?;
+            if !submission.known {
+                guesses.save(parser::YEAR)?;
+            }
+            if submission.verdict == Verdict::Correct {
+                answers.save(parser::YEAR)?;
+            }
+
+            print(cli.format, &[&submission], |submission| {
+                format!(
+                    "Day {} - Part {}: {} is {}{}",
+                    submission.day,
+                    submission.part,
+                    submission.answer,
+                    submission.verdict,
+                    if submission.known {
+                        ", as earlier guesses show, so it was not sent"
+                    } else {
+                        ""
+                    }
+                )
+            })?;
+            return Ok(submission.verdict == Verdict::Correct);
+        }
+        Command::Encrypt => {
+            let key = match Key::from_env()? {
+                Some(key) => key,
+                None => {
+                    let path = crypt::key_path();
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    let text = Key::generate()?;
+                    fs::write(&path, &text)?;
+                    eprintln!(
+                        "Made a new key in {}; keep it safe and out of the repository",
+                        path.display()
+                    );
+                    Key::new(&text)
+                }
+            };
+            let written = crypt::encrypt_dir(&parser::input_dir(), &key)?;
+            print(cli.format, &written, |path| {
+                format!("Encrypted {}", path.display())
+            })?;
+        }
+        Command::Decrypt => {
+            let key = Key::from_env()?.ok_or_else(|| {
+                format!(
+                    "no key: set {} or write one to {}",
+                    crypt::KEY_VAR,
+                    crypt::key_path().display()
+                )
+            })?;
+            let written = crypt::decrypt_dir(&parser::input_dir(), &key)?;
+            print(cli.format, &written, |path| {
+                format!("Decrypted {}", path.display())
+            })?;
+        }
+        Command::NewDay { day } => {
+            let written = scaffold::new_day(
+                &scaffold::source_dir(),
+                &parser::input_dir(),
+                parser::YEAR,
+                day,
+            )?;
+            print(cli.format, &written, |path| {
+                format!("Wrote {}", path.display())
+            })?;
+        }
+    }
+
+    Ok(true)
+}
+
+#[derive(Serialize)]
+struct Fetch {
+    day: usize,
+    fetched: Fetched,
+}
+
+#[derive(Serialize)]
+struct Listing {
+    day: usize,
+    /// The names of each part's implementations, default first.
+    parts: Vec<Vec<&'static str>>,
+}
+
+/// The requested day, or every registered day if none was requested.
+fn selected(day: Option<usize>) -> Result<Vec<Day>, String> {
+    match day {
+        Some(day) => registry::day(day)
+            .map(|registered| vec![registered])
+            .ok_or_else(|| format!("day {} is not registered", day)),
+        None => Ok(registry::days()),
+    }
+}
+
+/// The requested part of `day`, or all of its parts if none was requested.
+fn selected_parts(day: &Day, part: Option<usize>) -> Result<Vec<usize>, String> {
+    match runner::parts(day, part) {
+        parts if parts.is_empty() => Err(format!(
+            "day {} has no part {}",
+            day.day,
+            part.unwrap_or_default()
+        )),
+        parts => Ok(parts),
+    }
+}
+
+/// A day's input, or `None` after noting why it was skipped if it's missing or locked.
+fn available_input(day: usize) -> Result<Option<String>, InputError> {
+    match parser::load_input(day) {
+        Ok(input) => Ok(Some(input)),
+        Err(error @ (InputError::Missing { .. } | InputError::Locked { .. })) => {
+            eprintln!("skipping: {}", error);
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+fn read_input(path: &PathBuf) -> io::Result<String> {
+    if path.as_os_str() == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn print<T: Serialize>(
+    format: Format,
+    items: &[T],
+    line: impl Fn(&T) -> String,
+) -> serde_json::Result<()> {
+    match format {
+        Format::Text => items.iter().for_each(|item| println!("{}", line(item))),
+        Format::Json => println!("{}", serde_json::to_string_pretty(items)?),
+    }
+    Ok(())
+}
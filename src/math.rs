@@ -0,0 +1,114 @@
+/// Decimal digit manipulation without going through strings.
+///
+/// Numbers are expected to be non-negative. Signs are ignored when counting digits, and
+/// concatenating or splitting negative numbers gives meaningless results.
+pub trait Digits: Sized {
+    /// The number of decimal digits, where zero has one.
+    fn digits(self) -> u32;
+
+    /// The number written as this one's digits followed by `other`'s, as `12.concat(345)`
+    /// gives `12345`. Panics on overflow.
+    fn concat(self, other: Self) -> Self;
+
+    /// [`concat`](Digits::concat), or `None` on overflow.
+    fn checked_concat(self, other: Self) -> Option<Self>;
+
+    /// Split off the last `count` digits, as `12345.split_digits(2)` gives `(123, 45)`.
+    fn split_digits(self, count: u32) -> (Self, Self);
+}
+
+macro_rules! digits {
+    ($($integer:ty),+) => {
+        $(impl Digits for $integer {
+            fn digits(self) -> u32 {
+                self.abs_diff(0).checked_ilog10().map_or(1, |log| log + 1)
+            }
+
+            fn concat(self, other: Self) -> Self {
+                self.checked_concat(other)
+                    .expect("Concatenation should not overflow")
+            }
+
+            fn checked_concat(self, other: Self) -> Option<Self> {
+                (10 as $integer)
+                    .checked_pow(other.digits())?
+                    .checked_mul(self)?
+                    .checked_add(other)
+            }
+
+            fn split_digits(self, count: u32) -> (Self, Self) {
+                match (10 as $integer).checked_pow(count) {
+                    Some(divisor) => (self / divisor, self % divisor),
+                    // Too many digits to fit, so there are certainly none left over.
+                    None => (0, self),
+                }
+            }
+        })+
+    };
+}
+
+digits!(u32, u64, usize, i32, i64, isize);
+
+/// The greatest common divisor, which is zero only if both are.
+pub fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The lowest common multiple, which is zero if either is. Panics on overflow.
+pub fn lcm(a: usize, b: usize) -> usize {
+    checked_lcm(a, b).expect("Lowest common multiple should not overflow")
+}
+
+/// [`lcm`], or `None` on overflow.
+pub fn checked_lcm(a: usize, b: usize) -> Option<usize> {
+    match gcd(a, b) {
+        0 => Some(0),
+        divisor => (a / divisor).checked_mul(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits() {
+        assert_eq!(0usize.digits(), 1);
+        assert_eq!(9usize.digits(), 1);
+        assert_eq!(10usize.digits(), 2);
+        assert_eq!(usize::MAX.digits(), usize::MAX.to_string().len() as u32);
+        assert_eq!((-120isize).digits(), 3);
+        assert_eq!(i64::MIN.digits(), 19);
+    }
+
+    #[test]
+    fn concat() {
+        assert_eq!(12usize.concat(345), 12345);
+        assert_eq!(6isize.concat(0), 60);
+        assert_eq!(0u32.concat(7), 7);
+        assert_eq!(u32::MAX.checked_concat(1), None);
+        assert_eq!(429496729u32.checked_concat(5), Some(4294967295));
+        assert_eq!(429496729u32.checked_concat(6), None);
+    }
+
+    #[test]
+    fn split_digits() {
+        assert_eq!(12345usize.split_digits(2), (123, 45));
+        assert_eq!(1000usize.split_digits(2), (10, 0));
+        assert_eq!(7usize.split_digits(0), (7, 0));
+        assert_eq!(u64::MAX.split_digits(30), (0, u64::MAX));
+    }
+
+    #[test]
+    fn divisors() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        assert_eq!(checked_lcm(usize::MAX, usize::MAX - 1), None);
+    }
+}
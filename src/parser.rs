@@ -1,6 +1,554 @@
-use std::fs;
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-pub fn load_input(day: usize) -> String {
-    let path = format!("input/2024/day{}.txt", day);
-    fs::read_to_string(path).expect("Unable to open file")
+use crate::crypt::{self, CryptError, Key};
+
+pub const YEAR: usize = 2024;
+
+/// Environment variable naming the directory holding each year's inputs. Defaults to the
+/// repository's `input` directory, wherever the process is run from.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+#[derive(Debug)]
+pub enum InputError {
+    Missing {
+        year: usize,
+        day: usize,
+        path: PathBuf,
+    },
+    Unreadable {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Only an encrypted input, with no key to decrypt it.
+    Locked {
+        year: usize,
+        day: usize,
+        path: PathBuf,
+    },
+    Undecryptable {
+        path: PathBuf,
+        source: CryptError,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Missing { year, day, path } => write!(
+                f,
+                "input missing for day {} of {}, expected at {}",
+                day,
+                year,
+                path.display()
+            ),
+            InputError::Unreadable { path, source } => {
+                write!(f, "unable to read {}: {}", path.display(), source)
+            }
+            InputError::Locked { year, day, path } => write!(
+                f,
+                "input for day {} of {} is encrypted at {}, but there is no key in {} or {}",
+                day,
+                year,
+                path.display(),
+                crypt::KEY_VAR,
+                crypt::key_path().display()
+            ),
+            InputError::Undecryptable { path, source } => {
+                write!(f, "unable to decrypt {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Missing { .. } | InputError::Locked { .. } => None,
+            InputError::Unreadable { source, .. } => Some(source),
+            InputError::Undecryptable { source, .. } => Some(source),
+        }
+    }
+}
+
+pub fn input_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_INPUT_DIR.with(|dir| dir.borrow().clone()) {
+        return dir;
+    }
+    env::var_os(INPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("input"))
+}
+
+pub fn input_path(year: usize, day: usize) -> PathBuf {
+    input_dir()
+        .join(year.to_string())
+        .join(format!("day{}.txt", day))
+}
+
+/// Load an input, decrypting it with the configured key if it's only stored encrypted.
+pub fn load_year_input(year: usize, day: usize) -> Result<String, InputError> {
+    load_from(year, day, input_path(year, day), Key::from_env)
+}
+
+/// Load the input at `path`, or else its encrypted form, with the key from `key`, which
+/// is only looked up if it's needed.
+fn load_from(
+    year: usize,
+    day: usize,
+    path: PathBuf,
+    key: impl FnOnce() -> io::Result<Option<Key>>,
+) -> Result<String, InputError> {
+    // An empty input is a placeholder, for one not yet fetched or kept encrypted.
+    match fs::read_to_string(&path) {
+        Ok(input) if !input.is_empty() => return Ok(input),
+        Ok(_) => (),
+        Err(source) if source.kind() == io::ErrorKind::NotFound => (),
+        Err(source) => return Err(InputError::Unreadable { path, source }),
+    }
+
+    let encrypted = crypt::encrypted_path(&path);
+    let sealed = match fs::read(&encrypted) {
+        Ok(sealed) => sealed,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => {
+            return Err(InputError::Missing { year, day, path })
+        }
+        Err(source) => {
+            return Err(InputError::Unreadable {
+                path: encrypted,
+                source,
+            })
+        }
+    };
+
+    let key = key()
+        .map_err(|source| InputError::Unreadable {
+            path: crypt::key_path(),
+            source,
+        })?
+        .ok_or_else(|| InputError::Locked {
+            year,
+            day,
+            path: encrypted.clone(),
+        })?;
+    key.decrypt(&sealed)
+        .and_then(|plain| String::from_utf8(plain).map_err(|_| CryptError::Malformed))
+        .map_err(|source| InputError::Undecryptable {
+            path: encrypted,
+            source,
+        })
+}
+
+pub fn load_input(day: usize) -> Result<String, InputError> {
+    load_year_input(YEAR, day)
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_INPUT_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Run `f` with `dir` as the input directory, as if `AOC_INPUT_DIR` named it, but only on
+/// this thread, so tests running alongside still see their own.
+#[cfg(test)]
+pub(crate) fn with_input_dir<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+    let previous = TEST_INPUT_DIR.with(|input| input.replace(Some(dir.to_path_buf())));
+    let result = f();
+    TEST_INPUT_DIR.with(|input| *input.borrow_mut() = previous);
+    result
+}
+
+/// A new, empty directory for a test to use as its input directory, so tests don't
+/// interfere with each other or the real inputs.
+#[cfg(test)]
+pub(crate) fn scratch_dir(name: &str) -> PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "aoc-24-rs-{}-{}-{}",
+        name,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("Scratch directory should be creatable");
+    dir
+}
+
+/// Malformed puzzle input, located by 1-based line and column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: usize,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {} line {} column {}: {} (found {:?})",
+            self.day, self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// How the values in a list are separated. Values are trimmed of surrounding whitespace
+/// whichever is used.
+#[derive(Clone, Copy, Debug)]
+pub enum Separator {
+    /// Any run of whitespace.
+    Whitespace,
+    Char(char),
+    Str(&'static str),
+}
+
+impl From<char> for Separator {
+    fn from(separator: char) -> Self {
+        Separator::Char(separator)
+    }
+}
+
+impl From<&'static str> for Separator {
+    fn from(separator: &'static str) -> Self {
+        Separator::Str(separator)
+    }
+}
+
+/// A tag, and how to parse the text following it.
+pub type Alternative<'a, 'f, T> = (&'f str, &'f dyn Fn(&'a str) -> Result<T, ParseError>);
+
+/// A day's puzzle input, kept whole so errors can report where in it they occurred.
+pub struct Source<'a> {
+    day: usize,
+    input: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: usize, input: &'a str) -> Self {
+        Self { day, input }
+    }
+
+    /// The input's lines with surrounding whitespace trimmed, skipping any leading and
+    /// trailing blank lines.
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> {
+        self.input.trim().lines().map(|line| line.trim())
+    }
+
+    /// An empty slice at the end of the input, for reporting that something is missing.
+    pub fn end(&self) -> &'a str {
+        &self.input[self.input.len()..]
+    }
+
+    /// An error about `token`, which must be a slice of the input.
+    pub fn error(&self, token: &str, reason: impl Into<String>) -> ParseError {
+        let offset = (token.as_ptr() as usize)
+            .checked_sub(self.input.as_ptr() as usize)
+            .filter(|offset| offset + token.len() <= self.input.len())
+            .expect("Token should be a slice of the input");
+        let before = &self.input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        ParseError {
+            day: self.day,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: token.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Parse `token` into a `T`, describing the expected value as `what` on failure.
+    pub fn parse<T: FromStr>(&self, token: &'a str, what: &str) -> Result<T, ParseError> {
+        token
+            .parse()
+            .map_err(|_| self.error(token, format!("expected {}", what)))
+    }
+
+    /// The trimmed values in `text`, which must be a slice of the input.
+    pub fn split(
+        &self,
+        text: &'a str,
+        separator: impl Into<Separator>,
+    ) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        match separator.into() {
+            Separator::Whitespace => Box::new(text.split_whitespace()),
+            Separator::Char(c) => Box::new(text.split(c).map(str::trim)),
+            Separator::Str(s) => Box::new(text.split(s).map(str::trim)),
+        }
+    }
+
+    /// Parse every value in `text`, describing each expected value as `what`.
+    pub fn list<T: FromStr>(
+        &self,
+        text: &'a str,
+        separator: impl Into<Separator>,
+        what: &str,
+    ) -> Result<Vec<T>, ParseError> {
+        self.split(text, separator)
+            .map(|value| self.parse(value, what))
+            .collect()
+    }
+
+    /// Parse exactly `N` values in `text`, describing each expected value as `what`.
+    pub fn array<T: FromStr, const N: usize>(
+        &self,
+        text: &'a str,
+        separator: impl Into<Separator>,
+        what: &str,
+    ) -> Result<[T; N], ParseError> {
+        let values = self.list::<T>(text, separator, what)?;
+        let count = values.len();
+        values.try_into().map_err(|_| {
+            self.error(
+                text,
+                format!("expected {} values, each {}, found {}", N, what, count),
+            )
+        })
+    }
+
+    /// Parse a `key: values` line into its key and list of values.
+    pub fn key_values<K: FromStr, V: FromStr>(
+        &self,
+        line: &'a str,
+        key: &str,
+        separator: impl Into<Separator>,
+        what: &str,
+    ) -> Result<(K, Vec<V>), ParseError> {
+        let (name, values) = line
+            .split_once(':')
+            .ok_or_else(|| self.error(line, format!("expected {} followed by ':'", key)))?;
+        Ok((
+            self.parse(name.trim(), key)?,
+            self.list(values.trim(), separator, what)?,
+        ))
+    }
+
+    /// The input's lines, as for [`lines`](Source::lines), grouped into the sections
+    /// separated by blank lines.
+    pub fn sections(&self) -> Vec<Vec<&'a str>> {
+        let mut sections = vec![Vec::new()];
+        for line in self.lines() {
+            match line.is_empty() {
+                true if !sections[sections.len() - 1].is_empty() => sections.push(Vec::new()),
+                true => (),
+                false => sections
+                    .last_mut()
+                    .expect("Should have a section")
+                    .push(line),
+            }
+        }
+        sections.retain(|section| !section.is_empty());
+        sections
+    }
+
+    /// Exactly `N` sections, naming them as `what` if there are a different number.
+    pub fn sections_exact<const N: usize>(
+        &self,
+        what: &str,
+    ) -> Result<[Vec<&'a str>; N], ParseError> {
+        self.sections()
+            .try_into()
+            .map_err(|_| self.error(self.end(), format!("expected {}", what)))
+    }
+
+    /// Parse `text` with the first alternative whose tag it starts with, passing it the
+    /// text after the tag.
+    pub fn tagged<T>(
+        &self,
+        text: &'a str,
+        alternatives: &[Alternative<'a, '_, T>],
+    ) -> Result<T, ParseError> {
+        alternatives
+            .iter()
+            .find_map(|(tag, parse)| text.strip_prefix(tag).map(parse))
+            .unwrap_or_else(|| {
+                let tags = alternatives
+                    .iter()
+                    .map(|(tag, _)| format!("{:?}", tag))
+                    .collect::<Vec<_>>();
+                Err(self.error(text, format!("expected one of {}", tags.join(", "))))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST: &str = "
+        1 2
+        3 x
+    ";
+
+    #[test]
+    fn error_position() {
+        let source = Source::new(1, TEST);
+        let line = source.lines().nth(1).unwrap();
+        let token = line.split(' ').nth(1).unwrap();
+
+        let error = source.parse::<usize>(token, "a number").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                day: 1,
+                line: 3,
+                column: 11,
+                text: "x".to_string(),
+                reason: "expected a number".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "day 1 line 3 column 11: expected a number (found \"x\")"
+        );
+    }
+
+    #[test]
+    fn missing_input() {
+        let error = load_year_input(1999, 26).unwrap_err();
+        assert!(matches!(
+            error,
+            InputError::Missing {
+                year: 1999,
+                day: 26,
+                ..
+            }
+        ));
+        assert!(error
+            .to_string()
+            .starts_with("input missing for day 26 of 1999"));
+    }
+
+    #[test]
+    fn lists() {
+        let source = Source::new(0, "1 2  3\n4, 5,x\n7|8\n9|10|11");
+        let lines = source.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            source.list::<u8>(lines[0], Separator::Whitespace, "a number"),
+            Ok(vec![1, 2, 3])
+        );
+        let error = source.list::<u8>(lines[1], ',', "a number").unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 6, "x"));
+
+        assert_eq!(source.array::<u8, 2>(lines[2], '|', "a page"), Ok([7, 8]));
+        let error = source.array::<u8, 2>(lines[3], '|', "a page").unwrap_err();
+        assert_eq!(error.reason, "expected 2 values, each a page, found 3");
+        assert_eq!(error.text, "9|10|11");
+    }
+
+    #[test]
+    fn key_values() {
+        let source = Source::new(0, "190: 10 19\n83 17");
+        let lines = source.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            source.key_values::<u32, u32>(lines[0], "a target", Separator::Whitespace, "a number"),
+            Ok((190, vec![10, 19]))
+        );
+        let error = source
+            .key_values::<u32, u32>(lines[1], "a target", Separator::Whitespace, "a number")
+            .unwrap_err();
+        assert_eq!(error.reason, "expected a target followed by ':'");
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn sections() {
+        let source = Source::new(0, TEST);
+        assert_eq!(source.sections(), vec![vec!["1 2", "3 x"]]);
+
+        let source = Source::new(0, "a\nb\n  \n\nc\n");
+        assert_eq!(source.sections(), vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(
+            source.sections_exact::<2>("two sections"),
+            Ok([vec!["a", "b"], vec!["c"]])
+        );
+        let error = source.sections_exact::<3>("three sections").unwrap_err();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (6, "expected three sections")
+        );
+    }
+
+    #[test]
+    fn tagged() {
+        let source = Source::new(0, "on 3\noff\ntoggle");
+        let parse = |line| {
+            source.tagged(
+                line,
+                &[
+                    ("on ", &|rest| source.parse(rest, "a brightness")),
+                    ("off", &|_| Ok(0)),
+                ],
+            )
+        };
+        let lines = source.lines().collect::<Vec<_>>();
+
+        assert_eq!(parse(lines[0]), Ok(3u8));
+        assert_eq!(parse(lines[1]), Ok(0));
+        let error = parse(lines[2]).unwrap_err();
+        assert_eq!(error.reason, "expected one of \"on \", \"off\"");
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn error_at_end() {
+        let source = Source::new(2, "ab\ncd");
+        let error = source.error(source.end(), "missing value");
+        assert_eq!((error.line, error.column), (2, 3));
+    }
+
+    #[test]
+    fn encrypted_input() {
+        let dir = scratch_dir("encrypted-input");
+        let path = dir.join("day1.txt");
+        let key = Key::new("secret");
+        let with = |text: &str| {
+            let key = Key::new(text);
+            move || Ok(Some(key))
+        };
+        assert!(matches!(
+            load_from(2024, 1, path.clone(), with("secret")),
+            Err(InputError::Missing { .. })
+        ));
+        fs::write(&path, "").unwrap();
+        assert!(matches!(
+            load_from(2024, 1, path.clone(), with("secret")),
+            Err(InputError::Missing { .. })
+        ));
+
+        fs::write(crypt::encrypted_path(&path), key.encrypt(b"3   4\n")).unwrap();
+        assert_eq!(
+            load_from(2024, 1, path.clone(), with("secret")).unwrap(),
+            "3   4\n"
+        );
+        assert!(matches!(
+            load_from(2024, 1, path.clone(), || Ok(None)),
+            Err(InputError::Locked { .. })
+        ));
+        assert!(matches!(
+            load_from(2024, 1, path.clone(), with("wrong")),
+            Err(InputError::Undecryptable { .. })
+        ));
+
+        // A plain input is preferred, without needing a key.
+        fs::write(&path, "plain\n").unwrap();
+        assert_eq!(
+            load_from(2024, 1, path, || panic!("key looked up")).unwrap(),
+            "plain\n"
+        );
+    }
 }
@@ -0,0 +1,147 @@
+use std::ops::Range;
+
+#[cfg(test)]
+use crate::{Answer, Extras};
+
+/// A small, seedable pseudo-random number generator (SplitMix64), so generated inputs
+/// are the same on every machine and every run.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`. Panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Bound should be positive");
+        // Scaling rather than taking the remainder avoids favouring small numbers.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    /// A number in `range`. Panics if it's empty.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        range.start + self.below(range.len())
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    /// One of `items`, chosen uniformly. Panics if there are none.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// The side of the smallest square grid with at least `cells` cells, and at least one.
+pub fn side(cells: usize) -> usize {
+    let side = cells.max(1).isqrt();
+    if side * side < cells {
+        side + 1
+    } else {
+        side
+    }
+}
+
+/// A square grid of text with at least `cells` cells, each chosen by `cell` from its row
+/// and column, in reading order.
+pub fn grid(cells: usize, mut cell: impl FnMut(usize, usize) -> char) -> String {
+    let side = side(cells);
+    (0..side)
+        .map(|row| (0..side).map(|col| cell(row, col)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Check `solve` against a slower but obviously correct `oracle` on `cases` inputs of up
+/// to `size` from [`Extras::generate`], panicking with the seed and input of the first
+/// on which they disagree.
+#[cfg(test)]
+pub(crate) fn check_oracle<S: Extras>(
+    solve: impl Fn(&S::Input) -> S::Answer,
+    oracle: impl Fn(&S::Input) -> S::Answer,
+    cases: u64,
+    size: usize,
+) {
+    for seed in 0..cases {
+        let mut rng = Rng::new(seed);
+        // Small inputs are the likeliest to hit edge cases, so cover every size up to the
+        // largest.
+        let size = rng.range(1..size + 1);
+        let input = S::generate(&mut rng, size).expect("Day should have a generator");
+        let parsed = S::parse(&input)
+            .unwrap_or_else(|error| panic!("seed {} generated an invalid input: {}", seed, error));
+
+        let (actual, expected): (Answer, Answer) = (solve(&parsed).into(), oracle(&parsed).into());
+        assert_eq!(
+            actual,
+            expected,
+            "day {} disagrees with its oracle for seed {} on:\n{}",
+            S::DAY,
+            seed,
+            input
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut rng = Rng::new(1234567);
+        // The first outputs of the reference SplitMix64 implementation for this seed.
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+
+        let sample = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..20).map(|_| rng.range(5..10)).collect::<Vec<_>>()
+        };
+        assert_eq!(sample(7), sample(7));
+        assert_ne!(sample(7), sample(8));
+        assert!(sample(7).iter().all(|n| (5..10).contains(n)));
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut rng = Rng::new(0);
+        let mut items = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..10).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+
+        assert!((0..100).filter(|_| rng.chance(0.0)).count() == 0);
+        assert!((0..100).all(|_| rng.chance(1.0)));
+    }
+
+    #[test]
+    fn grids() {
+        assert_eq!([0, 1, 2, 4, 5, 9, 10].map(side), [1, 1, 2, 2, 3, 3, 4]);
+        assert_eq!(
+            grid(3, |row, col| if row == col { '#' } else { '.' }),
+            "#.\n.#"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use crate::{
+    animation::Recording, answers::Answer, parser::ParseError, random::Rng, render::Canvas,
+};
+
+/// Parses a raw input, only to check it's valid.
+pub type Checker = fn(&str) -> Result<(), ParseError>;
+
+/// Parses a raw input and solves one part of a day.
+pub type Runner = fn(&str) -> Result<Answer, ParseError>;
+
+/// Parses a raw input once and solves every part of a day, timing each phase.
+pub type Profiler = fn(&str) -> Result<Phases, ParseError>;
+
+/// Parses a raw input and draws the day's state, if it has one to draw.
+pub type Renderer = fn(&str) -> Result<Option<Canvas>, ParseError>;
+
+/// Parses a raw input and records the day's simulation, returning whether it has one.
+pub type Animator = fn(&str, &mut Recording) -> Result<bool, ParseError>;
+
+/// Makes up a random input of roughly the given size, if the day can.
+pub type Generator = fn(&mut Rng, usize) -> Option<String>;
+
+pub struct Phases {
+    pub parse: Duration,
+    /// The answer and solve time of each part, in part order.
+    pub parts: Vec<(Answer, Duration)>,
+}
+
+/// One way of solving a part, named after its function.
+#[derive(Clone, Copy)]
+pub struct Implementation {
+    pub name: &'static str,
+    pub run: Runner,
+}
+
+pub struct Day {
+    pub day: usize,
+    /// Each part's implementations, the first of which is the default.
+    pub parts: Vec<Vec<Implementation>>,
+    pub check: Checker,
+    pub profile: Profiler,
+    pub render: Renderer,
+    pub animate: Animator,
+    pub generate: Generator,
+}
+
+impl Day {
+    /// The default implementation of `part`.
+    pub fn part(&self, part: usize) -> Option<Runner> {
+        self.implementations(part)
+            .first()
+            .map(|implementation| implementation.run)
+    }
+
+    pub fn implementations(&self, part: usize) -> &[Implementation] {
+        part.checked_sub(1)
+            .and_then(|index| self.parts.get(index))
+            .map_or(&[], |implementations| implementations.as_slice())
+    }
+}
+
+/// Expose a [`Solution`](crate::Solution)'s solved parts, in part order, as a [`Day`].
+///
+/// Alternative implementations of a part are associated functions taking the same input,
+/// following its default and separated by `|`, as in
+/// `register!(Day11, part1 | part1_counted, part2)`.
+macro_rules! register {
+    (@implementation $solution:ident, $part:ident) => {
+        $crate::registry::Implementation {
+            name: stringify!($part),
+            run: |input| {
+                let parsed = <$solution as $crate::Solution>::parse(input)?;
+                Ok($crate::Answer::from($solution::$part(&parsed)))
+            },
+        }
+    };
+    ($solution:ident, $($part:ident $(| $alternative:ident)*),+) => {
+        pub(crate) fn register() -> $crate::registry::Day {
+            $crate::registry::Day {
+                day: <$solution as $crate::Solution>::DAY,
+                parts: vec![$(vec![
+                    $crate::registry::register!(@implementation $solution, $part),
+                    $($crate::registry::register!(@implementation $solution, $alternative)),*
+                ]),+],
+                check: |input| <$solution as $crate::Solution>::parse(input).map(drop),
+                profile: |input| {
+                    let start = std::time::Instant::now();
+                    let parsed = <$solution as $crate::Solution>::parse(input)?;
+                    let parse_time = start.elapsed();
+
+                    Ok($crate::registry::Phases {
+                        parse: parse_time,
+                        parts: vec![$({
+                            let start = std::time::Instant::now();
+                            let answer = $crate::Answer::from($solution::$part(&parsed));
+                            (answer, start.elapsed())
+                        }),+],
+                    })
+                },
+                render: |input| {
+                    let parsed = <$solution as $crate::Solution>::parse(input)?;
+                    Ok(<$solution as $crate::Extras>::render(&parsed))
+                },
+                animate: |input, recording| {
+                    let parsed = <$solution as $crate::Solution>::parse(input)?;
+                    Ok(<$solution as $crate::Extras>::animate(&parsed, recording))
+                },
+                generate: <$solution as $crate::Extras>::generate,
+            }
+        }
+    };
+}
+
+pub(crate) use register;
+
+/// Every registered day, in day order.
+pub fn days() -> Vec<Day> {
+    vec![
+        crate::day1::register(),
+        crate::day2::register(),
+        crate::day3::register(),
+        crate::day4::register(),
+        crate::day5::register(),
+        crate::day6::register(),
+        crate::day7::register(),
+        crate::day8::register(),
+        crate::day9::register(),
+        crate::day10::register(),
+        crate::day11::register(),
+        crate::day12::register(),
+    ]
+}
+
+pub fn day(day: usize) -> Option<Day> {
+    days().into_iter().find(|registered| registered.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{self, InputError};
+
+    #[test]
+    fn generated_inputs_parse() {
+        for day in days() {
+            for size in [1, 10, 100] {
+                let input = (day.generate)(&mut Rng::new(size as u64), size)
+                    .unwrap_or_else(|| panic!("day {} should have a generator", day.day));
+                assert!(
+                    (day.check)(&input).is_ok(),
+                    "day {} generated an invalid input of size {}:\n{}",
+                    day.day,
+                    size,
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn implementations_agree() {
+        for day in days() {
+            let input = match parser::load_input(day.day) {
+                Ok(input) => input,
+                Err(error @ (InputError::Missing { .. } | InputError::Locked { .. })) => {
+                    eprintln!("skipping: {}", error);
+                    continue;
+                }
+                Err(error) => panic!("{}", error),
+            };
+
+            for (index, implementations) in day.parts.iter().enumerate() {
+                let (default, alternatives) = implementations.split_first().unwrap();
+                let want = (default.run)(&input).unwrap();
+                for alternative in alternatives {
+                    assert_eq!(
+                        (alternative.run)(&input).unwrap(),
+                        want,
+                        "day {} part {}: {} disagrees with {}",
+                        day.day,
+                        index + 1,
+                        alternative.name,
+                        default.name
+                    );
+                }
+            }
+        }
+    }
+}
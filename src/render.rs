@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use crate::{geometry::Point, grid::Grid};
+
+/// An RGB colour.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Colour {
+    pub const BLACK: Colour = Colour::new(0, 0, 0);
+    pub const GREY: Colour = Colour::new(96, 96, 96);
+    pub const WHITE: Colour = Colour::new(255, 255, 255);
+    pub const RED: Colour = Colour::new(220, 50, 47);
+    pub const GREEN: Colour = Colour::new(133, 153, 0);
+    pub const YELLOW: Colour = Colour::new(181, 137, 0);
+    pub const BLUE: Colour = Colour::new(38, 139, 210);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// The `index`th of an endless sequence of colours, each easy to tell from the last few.
+    pub fn palette(index: usize) -> Self {
+        // Stepping round the hue circle by the golden angle never quite repeats.
+        let hue = (index as f64 * 137.508) % 360.0;
+        let sector = hue / 60.0;
+        let rising = (255.0 * (1.0 - (sector % 2.0 - 1.0).abs())) as u8;
+        match sector as usize {
+            0 => Colour::new(255, rising, 0),
+            1 => Colour::new(rising, 255, 0),
+            2 => Colour::new(0, 255, rising),
+            3 => Colour::new(0, rising, 255),
+            4 => Colour::new(rising, 0, 255),
+            _ => Colour::new(255, 0, rising),
+        }
+    }
+}
+
+/// How to draw one cell: a char for text, in a colour for terminals and images.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Glyph {
+    pub symbol: char,
+    pub colour: Colour,
+}
+
+impl Glyph {
+    pub const fn new(symbol: char, colour: Colour) -> Self {
+        Self { symbol, colour }
+    }
+}
+
+/// A grid state ready to be rendered.
+pub type Canvas = Grid<Glyph>;
+
+/// Draw each cell of `grid` with `f`, given its point.
+pub fn draw<T>(grid: &Grid<T>, mut f: impl FnMut(Point, &T) -> Glyph) -> Canvas {
+    Grid::new(
+        grid.width(),
+        grid.height(),
+        grid.iter().map(|(point, cell)| f(point, cell)).collect(),
+    )
+}
+
+/// The canvas's symbols, one line per row, as in the puzzles' own illustrations.
+pub fn plain(canvas: &Canvas) -> String {
+    canvas
+        .rows()
+        .map(|row| row.iter().map(|glyph| glyph.symbol).collect::<String>() + "\n")
+        .collect()
+}
+
+/// The canvas's symbols coloured with 24-bit ANSI escape codes, for a terminal.
+pub fn ansi(canvas: &Canvas) -> String {
+    let mut text = String::new();
+    for row in canvas.rows() {
+        let mut current = None;
+        for glyph in row {
+            if current != Some(glyph.colour) {
+                let Colour { red, green, blue } = glyph.colour;
+                write!(text, "\x1b[38;2;{};{};{}m", red, green, blue)
+                    .expect("Writing to a string should not fail");
+                current = Some(glyph.colour);
+            }
+            text.push(glyph.symbol);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// The canvas's colours as RGB pixels, each cell a `scale` pixel square. Returns the
+/// image's width and height with its pixels.
+pub(crate) fn pixels(canvas: &Canvas, scale: usize) -> (usize, usize, Vec<u8>) {
+    let scale = scale.max(1);
+    let mut pixels = Vec::with_capacity(canvas.width() * canvas.height() * scale * scale * 3);
+    for row in canvas.rows() {
+        for _ in 0..scale {
+            for glyph in row {
+                let Colour { red, green, blue } = glyph.colour;
+                for _ in 0..scale {
+                    pixels.extend([red, green, blue]);
+                }
+            }
+        }
+    }
+    (canvas.width() * scale, canvas.height() * scale, pixels)
+}
+
+/// The canvas as a binary PPM image, each cell a `scale` pixel square.
+pub fn ppm(canvas: &Canvas, scale: usize) -> Vec<u8> {
+    let (width, height, pixels) = pixels(canvas, scale);
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    image.extend(pixels);
+    image
+}
+
+/// The canvas as a PNG image, each cell a `scale` pixel square.
+pub fn png(canvas: &Canvas, scale: usize) -> Result<Vec<u8>, png::EncodingError> {
+    let (width, height, pixels) = pixels(canvas, scale);
+    let mut image = Vec::new();
+    let mut encoder = png::Encoder::new(&mut image, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)?;
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Source;
+
+    fn canvas() -> Canvas {
+        let grid = Grid::parse(&Source::new(0, "#.\n.#"), Some).unwrap();
+        draw(&grid, |_, c| match c {
+            '#' => Glyph::new('#', Colour::RED),
+            _ => Glyph::new('.', Colour::GREY),
+        })
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(plain(&canvas()), "#.\n.#\n");
+        assert_eq!(
+            ansi(&canvas()),
+            "\x1b[38;2;220;50;47m#\x1b[38;2;96;96;96m.\x1b[0m\n\
+             \x1b[38;2;96;96;96m.\x1b[38;2;220;50;47m#\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn images() {
+        let image = ppm(&canvas(), 2);
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 4 * 4 * 3);
+        // The second pixel of the first row is still in the first, red, cell.
+        assert_eq!(&image[header.len() + 3..header.len() + 6], &[220, 50, 47]);
+
+        let image = png(&canvas(), 3).unwrap();
+        let mut reader = png::Decoder::new(image.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 6));
+        assert_eq!(&pixels[3 * 3..3 * 4], &[96, 96, 96]);
+    }
+
+    #[test]
+    fn palette() {
+        let colours = (0..8).map(Colour::palette).collect::<Vec<_>>();
+        assert_eq!(colours[0], Colour::new(255, 0, 0));
+        for (i, colour) in colours.iter().enumerate() {
+            assert!(!colours[..i].contains(colour));
+        }
+    }
+}
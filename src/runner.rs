@@ -0,0 +1,626 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::{
+    answers::{Answer, Answers},
+    parser::{self, InputError, ParseError},
+    random::Rng,
+    registry::{Day, Implementation},
+};
+
+/// The answer to one part of a day, and how long it took to parse and solve.
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Answer,
+    #[serde(rename = "time_ns", serialize_with = "nanos")]
+    pub time: Duration,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Correct,
+    Incorrect,
+    Unanswered,
+    MissingInput,
+    Malformed,
+}
+
+/// The result of checking one part of a day against its recorded answer.
+#[derive(Debug, Serialize)]
+pub struct Verification {
+    pub day: usize,
+    pub part: usize,
+    pub status: Status,
+    pub expected: Option<Answer>,
+    pub actual: Option<Answer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Benchmark {
+    pub day: usize,
+    pub part: usize,
+    pub implementation: &'static str,
+    pub runs: usize,
+    #[serde(rename = "min_ns", serialize_with = "nanos")]
+    pub min: Duration,
+    #[serde(rename = "mean_ns", serialize_with = "nanos")]
+    pub mean: Duration,
+}
+
+/// How long a day took to parse its input and solve each part, the fastest of several runs.
+#[derive(Debug, Serialize)]
+pub struct Timing {
+    pub day: usize,
+    pub runs: usize,
+    #[serde(rename = "parse_ns", serialize_with = "nanos")]
+    pub parse: Duration,
+    #[serde(rename = "parts_ns", serialize_with = "all_nanos")]
+    pub parts: Vec<Duration>,
+}
+
+impl Timing {
+    pub fn total(&self) -> Duration {
+        self.parse + self.parts.iter().sum::<Duration>()
+    }
+}
+
+/// How long one part took to parse and solve generated inputs of increasing size.
+#[derive(Debug, Serialize)]
+pub struct Scaling {
+    pub day: usize,
+    pub part: usize,
+    /// The generator size giving an input about as long as a real one, which each
+    /// factor multiplies.
+    pub base: usize,
+    pub points: Vec<ScalePoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScalePoint {
+    pub factor: usize,
+    pub bytes: usize,
+    /// The fastest run, or `None` if it was skipped as likely to exceed the time budget.
+    #[serde(rename = "time_ns", serialize_with = "optional_nanos")]
+    pub time: Option<Duration>,
+}
+
+impl Scaling {
+    /// The exponent `k` in `time ∝ bytes^k` between the two largest inputs timed, so
+    /// 1 is linear and 2 quadratic.
+    pub fn growth(&self) -> Option<f64> {
+        growth(&self.points)
+    }
+}
+
+fn growth(points: &[ScalePoint]) -> Option<f64> {
+    let timed = points
+        .iter()
+        .filter_map(|point| point.time.map(|time| (point.bytes, time)))
+        .collect::<Vec<_>>();
+    match timed[..] {
+        [.., (small, faster), (large, slower)] if large > small => Some(
+            (slower.as_secs_f64() / faster.as_secs_f64().max(f64::MIN_POSITIVE)).ln()
+                / (large as f64 / small as f64).ln(),
+        ),
+        _ => None,
+    }
+}
+
+fn optional_nanos<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => nanos(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn all_nanos<S: serde::Serializer>(
+    durations: &[Duration],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(durations.iter().map(|duration| duration.as_nanos()))
+}
+
+fn nanos<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_nanos())
+}
+
+/// The parts of `day` to run: just `part` if given, otherwise all of them.
+pub fn parts(day: &Day, part: Option<usize>) -> Vec<usize> {
+    (1..=day.parts.len())
+        .filter(|index| part.is_none_or(|part| part == *index))
+        .collect()
+}
+
+pub fn run(day: &Day, part: usize, input: &str) -> Result<Outcome, ParseError> {
+    let runner = day.part(part).expect("Part should be registered");
+    let start = Instant::now();
+    let answer = runner(input)?;
+
+    Ok(Outcome {
+        day: day.day,
+        part,
+        answer,
+        time: start.elapsed(),
+    })
+}
+
+/// Solve `part`, or every part, of each day, with the input `load` gives for it. Days it
+/// has no input for, such as ones not fetched yet, are skipped.
+pub fn run_days<E: From<ParseError>>(
+    days: &[Day],
+    part: Option<usize>,
+    load: impl Fn(&Day) -> Result<Option<String>, E>,
+) -> Result<Vec<Outcome>, E> {
+    let mut outcomes = Vec::new();
+    for day in days {
+        let Some(input) = load(day)? else {
+            continue;
+        };
+        for part in parts(day, part) {
+            outcomes.push(run(day, part, &input)?);
+        }
+    }
+    Ok(outcomes)
+}
+
+/// [`run_days`], solving the days concurrently. Outcomes are still in day order, and each
+/// part's time is measured while the other days are being solved.
+#[cfg(feature = "parallel")]
+pub fn run_days_concurrently<E: From<ParseError> + Send>(
+    days: &[Day],
+    part: Option<usize>,
+    load: impl Fn(&Day) -> Result<Option<String>, E> + Sync,
+) -> Result<Vec<Outcome>, E> {
+    use rayon::prelude::*;
+
+    let outcomes = days
+        .par_iter()
+        .map(|day| run_days(std::slice::from_ref(day), part, &load))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(outcomes.into_iter().flatten().collect())
+}
+
+/// Check every registered part of `day` against `answers`, using the day's stored input.
+pub fn verify(day: &Day, answers: &Answers) -> Result<Vec<Verification>, InputError> {
+    let input = match parser::load_input(day.day) {
+        Ok(input) => Some(input),
+        Err(InputError::Missing { .. } | InputError::Locked { .. }) => None,
+        Err(error) => return Err(error),
+    };
+
+    Ok(parts(day, None)
+        .into_iter()
+        .map(|part| {
+            let expected = answers.get(day.day, part).cloned();
+            let (status, actual) = match (&input, &expected) {
+                (None, _) => (Status::MissingInput, None),
+                (Some(_), None) => (Status::Unanswered, None),
+                (Some(input), Some(expected)) => match run(day, part, input) {
+                    Ok(outcome) if outcome.answer == *expected => {
+                        (Status::Correct, Some(outcome.answer))
+                    }
+                    Ok(outcome) => (Status::Incorrect, Some(outcome.answer)),
+                    Err(_) => (Status::Malformed, None),
+                },
+            };
+
+            Verification {
+                day: day.day,
+                part,
+                status,
+                expected,
+                actual,
+            }
+        })
+        .collect())
+}
+
+pub fn bench(
+    day: &Day,
+    part: usize,
+    implementation: &Implementation,
+    input: &str,
+    runs: usize,
+) -> Result<Benchmark, ParseError> {
+    let times = (0..runs.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            (implementation.run)(input).map(|_| start.elapsed())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Benchmark {
+        day: day.day,
+        part,
+        implementation: implementation.name,
+        runs: times.len(),
+        min: *times.iter().min().expect("Should have run at least once"),
+        mean: times.iter().sum::<Duration>() / times.len() as u32,
+    })
+}
+
+pub fn time(day: &Day, input: &str, runs: usize) -> Result<Timing, ParseError> {
+    let mut timing = Timing {
+        day: day.day,
+        runs: runs.max(1),
+        parse: Duration::MAX,
+        parts: vec![Duration::MAX; day.parts.len()],
+    };
+
+    for _ in 0..timing.runs {
+        let phases = (day.profile)(input)?;
+        timing.parse = timing.parse.min(phases.parse);
+        for (fastest, (_, time)) in timing.parts.iter_mut().zip(phases.parts) {
+            *fastest = (*fastest).min(time);
+        }
+    }
+
+    Ok(timing)
+}
+
+/// About the smallest generator size whose input is at least `bytes` long, or `None` if
+/// the day has no generator.
+pub fn base_size(day: &Day, bytes: usize, seed: u64) -> Option<usize> {
+    let length = |size| (day.generate)(&mut Rng::new(seed), size).map(|input| input.len());
+
+    // Double until long enough, then bisect, taking lengths to grow with size.
+    let mut high = 1;
+    while length(high)? < bytes && high < 1 << 30 {
+        high *= 2;
+    }
+    let mut low = high / 2;
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if length(middle)? < bytes {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(high)
+}
+
+/// Time `part` of `day` on generated inputs of `base` multiplied by each of `factors`,
+/// taking the fastest of `runs`. Larger inputs are skipped once the last two timings
+/// predict they'd take longer than `budget`, so blowups are reported instead of waited
+/// out. Panics if the day has no generator.
+pub fn scale(
+    day: &Day,
+    part: usize,
+    base: usize,
+    factors: &[usize],
+    seed: u64,
+    runs: usize,
+    budget: Duration,
+) -> Result<Scaling, ParseError> {
+    let runner = day.part(part).expect("Part should be registered");
+    let mut points: Vec<ScalePoint> = Vec::new();
+
+    for &factor in factors {
+        let input = (day.generate)(&mut Rng::new(seed), base * factor)
+            .expect("Day should have a generator");
+
+        // Assume growth is at least linear, including before there's enough to tell.
+        let predicted = match points.last() {
+            None => Some(0.0),
+            Some(last) => last.time.map(|time| {
+                let exponent = growth(&points).unwrap_or(1.0).max(1.0);
+                time.as_secs_f64() * (input.len() as f64 / last.bytes as f64).powf(exponent)
+            }),
+        };
+        if predicted.is_none_or(|predicted| predicted > budget.as_secs_f64()) {
+            points.push(ScalePoint {
+                factor,
+                bytes: input.len(),
+                time: None,
+            });
+            continue;
+        }
+
+        let mut fastest = Duration::MAX;
+        for _ in 0..runs.max(1) {
+            let start = Instant::now();
+            runner(&input)?;
+            fastest = fastest.min(start.elapsed());
+        }
+        points.push(ScalePoint {
+            factor,
+            bytes: input.len(),
+            time: Some(fastest),
+        });
+    }
+
+    Ok(Scaling {
+        day: day.day,
+        part,
+        base,
+        points,
+    })
+}
+
+/// Lay out timings as a table with a column per phase and a final total row.
+pub fn timing_table(timings: &[Timing]) -> String {
+    let parts = timings
+        .iter()
+        .map(|timing| timing.parts.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut header = vec!["Day".to_string(), "Parse".to_string()];
+    header.extend((1..=parts).map(|part| format!("Part {}", part)));
+    header.push("Total".to_string());
+
+    let row = |label: String, parse: Duration, solves: &[Duration], total: Duration| {
+        let mut row = vec![label, format!("{:.2?}", parse)];
+        row.extend((0..parts).map(|part| {
+            solves
+                .get(part)
+                .map_or("-".to_string(), |time| format!("{:.2?}", time))
+        }));
+        row.push(format!("{:.2?}", total));
+        row
+    };
+
+    let mut rows = vec![header];
+    rows.extend(timings.iter().map(|timing| {
+        row(
+            timing.day.to_string(),
+            timing.parse,
+            &timing.parts,
+            timing.total(),
+        )
+    }));
+    rows.push(row(
+        "All".to_string(),
+        timings.iter().map(|timing| timing.parse).sum(),
+        &(0..parts)
+            .map(|part| {
+                timings
+                    .iter()
+                    .filter_map(|timing| timing.parts.get(part))
+                    .sum()
+            })
+            .collect::<Vec<_>>(),
+        timings.iter().map(|timing| timing.total()).sum(),
+    ));
+
+    table(&rows)
+}
+
+/// Lay out how each part's time grew with its input as a table, with a column per factor
+/// and the final growth rate.
+pub fn scaling_table(scalings: &[Scaling]) -> String {
+    let mut header = vec!["Day".to_string(), "Part".to_string(), "Bytes".to_string()];
+    if let Some(first) = scalings.first() {
+        header.extend(
+            first
+                .points
+                .iter()
+                .map(|point| format!("{}x", point.factor)),
+        );
+    }
+    header.push("Growth".to_string());
+
+    let mut rows = vec![header];
+    rows.extend(scalings.iter().map(|scaling| {
+        let mut row = vec![
+            scaling.day.to_string(),
+            scaling.part.to_string(),
+            scaling
+                .points
+                .first()
+                .map_or("-".to_string(), |point| point.bytes.to_string()),
+        ];
+        row.extend(scaling.points.iter().map(|point| {
+            point
+                .time
+                .map_or("-".to_string(), |time| format!("{:.2?}", time))
+        }));
+        row.push(
+            scaling
+                .growth()
+                .map_or("-".to_string(), |growth| format!("n^{:.1}", growth)),
+        );
+        row
+    }));
+
+    table(&rows)
+}
+
+/// Right-align each column of `rows`, separating them with bars.
+fn table(rows: &[Vec<String>]) -> String {
+    let widths = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::registry;
+
+    const TEST: &str = "
+        3   4
+        4   3
+        2   5
+        1   3
+        3   9
+        3   3
+    ";
+
+    #[test]
+    fn run_part() {
+        let day = registry::day(1).unwrap();
+        assert_eq!(parts(&day, None), vec![1, 2]);
+        assert_eq!(parts(&day, Some(2)), vec![2]);
+        assert_eq!(run(&day, 2, TEST).unwrap().answer, Answer::Integer(31));
+        assert!(run(&day, 1, "3 x").is_err());
+    }
+
+    #[test]
+    fn run_several_days() {
+        let days = [registry::day(1).unwrap(), registry::day(2).unwrap()];
+        let run = |missing: usize| {
+            run_days(&days, Some(1), |day| match day.day {
+                day if day == missing => Ok::<_, ParseError>(None),
+                1 => Ok(Some(TEST.to_string())),
+                _ => Ok(Some("1 2 3\n3 2 9".to_string())),
+            })
+            .unwrap()
+            .iter()
+            .map(|outcome| (outcome.day, outcome.part, outcome.answer.clone()))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            run(0),
+            vec![(1, 1, Answer::Integer(11)), (2, 1, Answer::Integer(1))]
+        );
+        // A day without an input doesn't stop the rest.
+        assert_eq!(run(1), vec![(2, 1, Answer::Integer(1))]);
+        assert!(run_days(&days, None, |day| match day.day {
+            1 => Ok::<_, ParseError>(Some("3 x".to_string())),
+            _ => Ok(None),
+        })
+        .is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn concurrent_days_agree() {
+        let days = registry::days();
+        let load = |day: &Day| Ok::<_, ParseError>(parser::load_input(day.day).ok());
+        let answers = |outcomes: Vec<Outcome>| {
+            outcomes
+                .into_iter()
+                .map(|outcome| (outcome.day, outcome.part, outcome.answer))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            answers(run_days_concurrently(&days, None, load).unwrap()),
+            answers(run_days(&days, None, load).unwrap())
+        );
+    }
+
+    #[test]
+    fn bench_part() {
+        let day = registry::day(1).unwrap();
+        let benchmark = bench(&day, 1, &day.implementations(1)[0], TEST, 3).unwrap();
+        assert_eq!(benchmark.implementation, "part1");
+        assert_eq!(benchmark.runs, 3);
+        assert!(benchmark.min <= benchmark.mean);
+    }
+
+    #[test]
+    fn time_phases() {
+        let day = registry::day(1).unwrap();
+        let timing = time(&day, TEST, 2).unwrap();
+        assert_eq!(timing.runs, 2);
+        assert_eq!(timing.parts.len(), 2);
+        assert!(timing.total() >= timing.parse);
+
+        let table = timing_table(&[timing]);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0]
+                .split('|')
+                .map(|cell| cell.trim())
+                .collect::<Vec<_>>(),
+            vec!["Day", "Parse", "Part 1", "Part 2", "Total"]
+        );
+        assert!(lines[2].trim_start().starts_with("All |"));
+    }
+
+    #[test]
+    fn scale_part() {
+        let day = registry::day(2).unwrap();
+        let base = base_size(&day, 1000, 0).unwrap();
+        let length = |size| (day.generate)(&mut Rng::new(0), size).unwrap().len();
+        assert!(length(base) >= 1000 && length(base - 1) < 1000);
+
+        let scaling = scale(&day, 1, base, &[1, 2], 0, 1, Duration::MAX).unwrap();
+        assert!(scaling.points.iter().all(|point| point.time.is_some()));
+        assert!(scaling.points[1].bytes > scaling.points[0].bytes);
+        assert!(scaling.growth().is_some());
+
+        // With no time to spare, nothing after the first size is worth trying.
+        let scaling = scale(&day, 1, base, &[1, 2, 4], 0, 1, Duration::ZERO).unwrap();
+        assert_eq!(
+            scaling
+                .points
+                .iter()
+                .map(|point| (point.factor, point.time.is_some()))
+                .collect::<Vec<_>>(),
+            vec![(1, true), (2, false), (4, false)]
+        );
+        assert_eq!(scaling.growth(), None);
+
+        let table = scaling_table(&[scaling]);
+        let header = table.lines().next().unwrap();
+        assert_eq!(
+            header
+                .split('|')
+                .map(|cell| cell.trim())
+                .collect::<Vec<_>>(),
+            vec!["Day", "Part", "Bytes", "1x", "2x", "4x", "Growth"]
+        );
+    }
+
+    #[test]
+    fn verify_day() {
+        let dir = parser::scratch_dir("verify");
+        fs::create_dir_all(dir.join("2024")).unwrap();
+        fs::write(dir.join("2024/day1.txt"), TEST).unwrap();
+        let day = registry::day(1).unwrap();
+        let mut answers = Answers::default();
+        answers.record(1, 1, 11.into());
+        answers.record(1, 2, 0.into());
+
+        let statuses = |dir: &Path, answers: &Answers| {
+            parser::with_input_dir(dir, || verify(&day, answers))
+                .unwrap()
+                .into_iter()
+                .map(|verification| verification.status)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            statuses(&dir, &answers),
+            [Status::Correct, Status::Incorrect]
+        );
+        assert_eq!(
+            statuses(&dir, &Answers::default()),
+            [Status::Unanswered, Status::Unanswered]
+        );
+        assert_eq!(
+            statuses(&dir.join("missing"), &answers),
+            [Status::MissingInput, Status::MissingInput]
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Creates the files for a new day: its module from a template, registered alongside the
+//! others, with placeholders for its input, answers and example.
+
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    answers::{Answers, AnswersError},
+    crypt,
+};
+
+/// A day's module, with every `{day}` replaced by its number. It builds and passes its tests
+/// as it is, answering 0 until each part is solved.
+const TEMPLATE: &str = r#"use crate::{
+    parser::{ParseError, Source},
+    random::Rng,
+    Extras, Solution,
+};
+
+pub struct Day{day};
+
+impl Solution for Day{day} {
+    const DAY: usize = {day};
+    type Input = Vec<String>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let source = Source::new(Self::DAY, input);
+        Ok(source.lines().map(str::to_string).collect())
+    }
+
+    fn part1(_input: &Self::Input) -> Self::Answer {
+        0
+    }
+
+    fn part2(_input: &Self::Input) -> Self::Answer {
+        0
+    }
+}
+
+impl Extras for Day{day} {
+    fn generate(rng: &mut Rng, size: usize) -> Option<String> {
+        Some((0..size).map(|_| format!("{}\n", rng.below(100))).collect())
+    }
+}
+
+crate::registry::register!(Day{day}, part1, part2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::examples::test_day!(Day{day});
+}
+"#;
+
+/// The name of a new day's placeholder example.
+const EXAMPLE: &str = "example";
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// Not a day of Advent.
+    InvalidDay(usize),
+    /// The day already has a module.
+    Exists(PathBuf),
+    /// A file the day is registered in doesn't list the others as expected.
+    Unregistrable(PathBuf),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Answers {
+        path: PathBuf,
+        source: AnswersError,
+    },
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScaffoldError::InvalidDay(day) => write!(f, "there is no day {} of Advent", day),
+            ScaffoldError::Exists(path) => write!(f, "{} already exists", path.display()),
+            ScaffoldError::Unregistrable(path) => {
+                write!(
+                    f,
+                    "unable to find where to register the day in {}",
+                    path.display()
+                )
+            }
+            ScaffoldError::Io { path, source } => {
+                write!(f, "unable to write {}: {}", path.display(), source)
+            }
+            ScaffoldError::Answers { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl Error for ScaffoldError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScaffoldError::InvalidDay(_)
+            | ScaffoldError::Exists(_)
+            | ScaffoldError::Unregistrable(_) => None,
+            ScaffoldError::Io { source, .. } => Some(source),
+            ScaffoldError::Answers { source, .. } => Some(source),
+        }
+    }
+}
+
+/// The repository's source directory, wherever the process is run from.
+pub fn source_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+/// Create day `day` of `year` from the template in `src`, registering it in `lib.rs` and
+/// `registry.rs`, and add its placeholders to the input directory `input`, returning every
+/// path written. Nothing in the input directory is overwritten, and nothing is written at
+/// all if the day already has a module.
+pub fn new_day(
+    src: &Path,
+    input: &Path,
+    year: usize,
+    day: usize,
+) -> Result<Vec<PathBuf>, ScaffoldError> {
+    if !(1..=25).contains(&day) {
+        return Err(ScaffoldError::InvalidDay(day));
+    }
+    let module = src.join(format!("day{}.rs", day));
+    if module.try_exists().map_err(io_error(&module))? {
+        return Err(ScaffoldError::Exists(module));
+    }
+
+    // Work out every change before making any, so a tree it can't register in is untouched.
+    let lib = src.join("lib.rs");
+    let lib_text = register(&lib, day, ("pub mod day", ";"))?;
+    let registry = src.join("registry.rs");
+    let registry_text = register(&registry, day, ("        crate::day", "::register(),"))?;
+
+    write(&module, &TEMPLATE.replace("{day}", &day.to_string()))?;
+    write(&lib, &lib_text)?;
+    write(&registry, &registry_text)?;
+    let mut written = vec![module, lib, registry];
+
+    // An empty input is a placeholder, which `fetch` replaces.
+    let year_dir = input.join(year.to_string());
+    let input_path = year_dir.join(format!("day{}.txt", day));
+    let exists = |path: &Path| path.try_exists().map_err(io_error(path));
+    if !exists(&input_path)? && !exists(&crypt::encrypted_path(&input_path))? {
+        create(&input_path, "")?;
+        written.push(input_path);
+    }
+
+    let answers_path = year_dir.join("answers.toml");
+    let answers_error = |source| ScaffoldError::Answers {
+        path: answers_path.clone(),
+        source,
+    };
+    let mut answers = match fs::read_to_string(&answers_path) {
+        Ok(text) => Answers::parse(&text).map_err(answers_error)?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Answers::default(),
+        Err(error) => return Err(io_error(&answers_path)(error)),
+    };
+    answers.reserve(day);
+    write(&answers_path, &answers.to_toml().map_err(answers_error)?)?;
+    written.push(answers_path);
+
+    let examples = year_dir.join("examples");
+    let example_path = examples
+        .join(format!("day{}", day))
+        .join(format!("{}.txt", EXAMPLE));
+    if !exists(&example_path)? {
+        create(&example_path, "")?;
+        written.push(example_path);
+    }
+    let manifest = examples.join("answers.toml");
+    let text = match fs::read_to_string(&manifest) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(io_error(&manifest)(error)),
+    };
+    if let Some(text) = reserve_example(&text, day) {
+        write(&manifest, &text)?;
+        written.push(manifest);
+    }
+
+    Ok(written)
+}
+
+/// The text of the file at `path` with a line for `day` added to the lines registering the
+/// days already there, each a day's number between `before` and `after`, all in day order.
+fn register(
+    path: &Path,
+    day: usize,
+    (before, after): (&str, &str),
+) -> Result<String, ScaffoldError> {
+    let text = fs::read_to_string(path).map_err(|source| ScaffoldError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let unregistrable = || ScaffoldError::Unregistrable(path.to_path_buf());
+    let registered = |text: &str| {
+        text.strip_prefix(before)?
+            .strip_suffix(after)?
+            .parse::<usize>()
+            .ok()
+    };
+
+    let mut lines = text.lines().collect::<Vec<_>>();
+    let first = lines
+        .iter()
+        .position(|text| registered(text).is_some())
+        .ok_or_else(unregistrable)?;
+    let mut days = lines
+        .iter()
+        .filter_map(|text| registered(text))
+        .collect::<Vec<_>>();
+    if days.contains(&day) {
+        return Err(unregistrable());
+    }
+    days.push(day);
+    days.sort_unstable();
+
+    lines.retain(|text| registered(text).is_none());
+    let days = days
+        .into_iter()
+        .map(|day| format!("{}{}{}", before, day, after))
+        .collect::<Vec<_>>();
+    lines.splice(first..first, days.iter().map(String::as_str));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// The examples manifest with an empty entry for a day's placeholder example before the
+/// first later day's, or `None` if the day already has examples.
+fn reserve_example(text: &str, day: usize) -> Option<String> {
+    let header = |text: &str| {
+        text.strip_prefix("[day")?
+            .split_once('.')?
+            .0
+            .parse::<usize>()
+            .ok()
+    };
+    if text.lines().any(|line| header(line) == Some(day)) {
+        return None;
+    }
+
+    let entry = format!("[day{}.{}]\n# part1 =\n# part2 =\n", day, EXAMPLE);
+    let mut lines = text.lines().collect::<Vec<_>>();
+    match lines
+        .iter()
+        .position(|line| header(line).is_some_and(|later| later > day))
+    {
+        Some(index) => lines.insert(index, &entry),
+        None => {
+            if lines.last().is_some_and(|line| !line.is_empty()) {
+                lines.push("");
+            }
+            lines.push(entry.trim_end());
+        }
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    Some(text)
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> ScaffoldError {
+    let path = path.to_path_buf();
+    move |source| ScaffoldError::Io { path, source }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    fs::write(path, contents).map_err(io_error(path))
+}
+
+/// Write a new file, creating its directory.
+fn create(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error(parent))?;
+    }
+    write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        examples::Example,
+        parser::{self, InputError},
+        registry,
+    };
+
+    const LIB: &str = "pub mod parser;\n\npub mod day1;\npub mod day10;\npub mod day2;\n\n\
+        pub use solution::Solution;\n";
+    const REGISTRY: &str = "pub fn days() -> Vec<Day> {\n    vec![\n        \
+        crate::day1::register(),\n        crate::day2::register(),\n        \
+        crate::day10::register(),\n    ]\n}\n";
+    const EXAMPLES: &str = "# Answers.\n\n[day1.example]\npart1 = 11\n\n\
+        [day10.larger]\npart1 = 36\n";
+
+    #[test]
+    fn new_day() {
+        let dir = parser::scratch_dir("scaffold");
+        let (src, input) = (dir.join("src"), dir.join("input"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(input.join("2024/examples")).unwrap();
+        fs::write(src.join("lib.rs"), LIB).unwrap();
+        fs::write(src.join("registry.rs"), REGISTRY).unwrap();
+        fs::write(input.join("2024/answers.toml"), "[day1]\npart1 = 11\n").unwrap();
+        fs::write(input.join("2024/examples/answers.toml"), EXAMPLES).unwrap();
+
+        let written = super::new_day(&src, &input, 2024, 3).unwrap();
+        assert_eq!(written.len(), 7);
+        let read = |path: &str| fs::read_to_string(dir.join(path)).unwrap();
+        assert!(read("src/day3.rs").contains("pub struct Day3;"));
+        assert!(read("src/day3.rs").contains("const DAY: usize = 3;"));
+        assert_eq!(
+            read("src/lib.rs"),
+            "pub mod parser;\n\npub mod day1;\npub mod day2;\npub mod day3;\npub mod day10;\n\n\
+             pub use solution::Solution;\n"
+        );
+        assert!(read("src/registry.rs").contains(
+            "day2::register(),\n        crate::day3::register(),\n        crate::day10::"
+        ));
+        assert_eq!(read("input/2024/day3.txt"), "");
+        assert_eq!(
+            Answers::parse(&read("input/2024/answers.toml"))
+                .unwrap()
+                .to_toml()
+                .unwrap(),
+            "[day1]\npart1 = 11\n\n[day3]\n"
+        );
+        assert_eq!(read("input/2024/examples/day3/example.txt"), "");
+        assert_eq!(
+            read("input/2024/examples/answers.toml"),
+            "# Answers.\n\n[day1.example]\npart1 = 11\n\n[day3.example]\n# part1 =\n# part2 =\n\n\
+             [day10.larger]\npart1 = 36\n"
+        );
+
+        assert!(matches!(
+            super::new_day(&src, &input, 2024, 3),
+            Err(ScaffoldError::Exists(_))
+        ));
+        assert!(matches!(
+            super::new_day(&src, &input, 2024, 26),
+            Err(ScaffoldError::InvalidDay(26))
+        ));
+
+        // A day after the rest goes last, and an input already there is kept.
+        fs::write(input.join("2024/day25.txt"), "real").unwrap();
+        let written = super::new_day(&src, &input, 2024, 25).unwrap();
+        assert!(!written.contains(&input.join("2024/day25.txt")));
+        assert_eq!(read("input/2024/day25.txt"), "real");
+        assert!(read("src/lib.rs").contains("pub mod day10;\npub mod day25;\n\n"));
+        assert!(read("input/2024/examples/answers.toml")
+            .ends_with("part1 = 36\n\n[day25.example]\n# part1 =\n# part2 =\n"));
+    }
+
+    #[test]
+    fn new_day_in_this_tree() {
+        let dir = parser::scratch_dir("scaffold-tree");
+        let (src, input) = (dir.join("src"), dir.join("input"));
+        let year = input.join(parser::YEAR.to_string());
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(year.join("examples")).unwrap();
+        for name in ["lib.rs", "registry.rs"] {
+            fs::copy(source_dir().join(name), src.join(name)).unwrap();
+        }
+        let real = parser::input_dir().join(parser::YEAR.to_string());
+        for name in ["answers.toml", "examples/answers.toml"] {
+            fs::copy(real.join(name), year.join(name)).unwrap();
+        }
+
+        let registered = registry::days()
+            .iter()
+            .map(|day| day.day)
+            .collect::<Vec<_>>();
+        let day = (1..=25).find(|day| !registered.contains(day)).unwrap();
+        super::new_day(&src, &input, parser::YEAR, day).unwrap();
+
+        let read = |path: &Path| fs::read_to_string(path).unwrap();
+        let module = read(&src.join(format!("day{}.rs", day)));
+        assert!(module.contains(&format!("impl Solution for Day{} {{", day)));
+        assert!(!module.contains("{day}") && !module.contains("todo!"));
+        let (lib, registry) = (read(&src.join("lib.rs")), read(&src.join("registry.rs")));
+        for day in registered.iter().chain([&day]) {
+            assert!(lib.contains(&format!("pub mod day{};\n", day)));
+            assert!(registry.contains(&format!("crate::day{}::register(),\n", day)));
+        }
+
+        // Its placeholders load, and are left out of the checks until they're filled in.
+        parser::with_input_dir(&input, || {
+            assert!(matches!(
+                parser::load_input(day),
+                Err(InputError::Missing { .. })
+            ));
+            let answers = Answers::load(parser::YEAR).unwrap();
+            assert_eq!((answers.get(day, 1), answers.get(day, 2)), (None, None));
+            let examples = Example::load(parser::YEAR, day).unwrap();
+            assert_eq!(examples.len(), 1);
+            assert!(examples[0].is_placeholder());
+            // The rest of the manifest still parses.
+            assert!(Example::load(parser::YEAR, 26).unwrap().is_empty());
+        });
+    }
+}
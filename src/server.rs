@@ -0,0 +1,481 @@
+//! A stand-in for the puzzle site, for the site client's tests.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::answers::{Answer, Answers};
+
+/// The largest request body to accept, far bigger than any guess.
+const MAX_BODY: usize = 1 << 16;
+
+/// A response's status, extra headers and body.
+type Reply = (u16, Vec<(&'static str, String)>, String);
+
+/// The start line, headers and body of a request.
+#[derive(Debug)]
+struct Request {
+    start: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Read one request, whose body is only as long as its `Content-Length` says.
+    fn read(stream: impl Read) -> io::Result<Self> {
+        let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let mut reader = BufReader::new(stream);
+        let mut line = || -> io::Result<String> {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            Ok(line.trim_end_matches(['\r', '\n']).to_string())
+        };
+
+        let start = line()?;
+        let mut headers = Vec::new();
+        loop {
+            let header = line()?;
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("header without a value: {}", header)))?;
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+
+        let mut request = Self {
+            start,
+            headers,
+            body: Vec::new(),
+        };
+        if let Some(length) = request.header("Content-Length") {
+            let length = length
+                .parse()
+                .ok()
+                .filter(|length| *length <= MAX_BODY)
+                .ok_or_else(|| invalid(format!("invalid length {}", length)))?;
+            request.body = vec![0; length];
+            reader.read_exact(&mut request.body)?;
+        }
+        Ok(request)
+    }
+
+    /// The value of the first header called `name`, ignoring case.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// What the stand-in site serves, and what it has been asked for.
+#[derive(Debug, Default)]
+pub struct Fixtures {
+    /// The session token every request must carry in its cookie.
+    pub session: String,
+    /// Puzzle inputs, keyed by year then day.
+    pub inputs: BTreeMap<(usize, usize), String>,
+    /// The answers guesses are judged against, keyed by year.
+    pub answers: BTreeMap<usize, Answers>,
+    /// The year, day and part of each puzzle solved so far.
+    pub solved: BTreeSet<(usize, usize, usize)>,
+    /// The shortest time to allow between requests, answering any sooner with a 429.
+    pub interval: Duration,
+    /// How long to refuse guesses for after a wrong one.
+    pub penalty: Duration,
+    /// The method and path of every request received, in order.
+    pub requests: Vec<String>,
+    last: Option<Instant>,
+    penalised_until: Option<Instant>,
+}
+
+impl Fixtures {
+    pub fn new(session: &str) -> Self {
+        Self {
+            session: session.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// How to answer `request`, as the real site would.
+    fn respond(&mut self, request: &Request) -> Reply {
+        let mut words = request.start.split_whitespace();
+        let (method, path) = (
+            words.next().unwrap_or_default(),
+            words.next().unwrap_or_default(),
+        );
+        self.requests.push(format!("{} {}", method, path));
+
+        let now = Instant::now();
+        if let Some(wait) = self
+            .last
+            .map(|last| self.interval.saturating_sub(now - last))
+            .filter(|wait| !wait.is_zero())
+        {
+            let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            let retry = vec![("Retry-After", seconds.to_string())];
+            return (429, retry, "Too many requests.\n".to_string());
+        }
+        self.last = Some(now);
+
+        let session = request
+            .header("Cookie")
+            .into_iter()
+            .flat_map(|cookie| cookie.split(';'))
+            .find_map(|cookie| cookie.trim().strip_prefix("session="));
+        let route = path
+            .strip_prefix('/')
+            .map(|path| path.split('/').collect::<Vec<_>>());
+
+        match route.as_deref() {
+            Some([year, "day", day, endpoint @ ("input" | "answer")]) => {
+                let expected = if *endpoint == "input" { "GET" } else { "POST" };
+                if method != expected {
+                    return (405, Vec::new(), "Method not allowed.\n".to_string());
+                }
+                if session != Some(self.session.as_str()) {
+                    return (
+                        400,
+                        Vec::new(),
+                        "Puzzle inputs differ by user.  Please log in to get your puzzle \
+                         input.\n"
+                            .to_string(),
+                    );
+                }
+                match (year.parse(), day.parse(), *endpoint) {
+                    (Ok(year), Ok(day), "input") => self.input(year, day),
+                    (Ok(year), Ok(day), _) => self.answer(year, day, &request.body, now),
+                    _ => (404, Vec::new(), "404 Not Found\n".to_string()),
+                }
+            }
+            _ => (404, Vec::new(), "404 Not Found\n".to_string()),
+        }
+    }
+
+    fn input(&self, year: usize, day: usize) -> Reply {
+        match self.inputs.get(&(year, day)) {
+            Some(input) => (200, Vec::new(), input.clone()),
+            None => (
+                404,
+                Vec::new(),
+                "Please don't repeatedly request this endpoint before it unlocks!\n".to_string(),
+            ),
+        }
+    }
+
+    /// Judge a submitted form, worded as the real site words it.
+    fn answer(&mut self, year: usize, day: usize, body: &[u8], now: Instant) -> Reply {
+        let page = |text: &str| {
+            let html = format!("<main>\n<article><p>{}</p></article>\n</main>\n", text);
+            (200, Vec::new(), html)
+        };
+        let form = parse_form(&String::from_utf8_lossy(body));
+        let field = |name| {
+            form.iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let (Some(part), Some(answer)) =
+            (field("level").and_then(|l| l.parse().ok()), field("answer"))
+        else {
+            return (400, Vec::new(), "Missing level or answer.\n".to_string());
+        };
+
+        if let Some(wait) = self
+            .penalised_until
+            .map(|until| until.saturating_duration_since(now))
+            .filter(|wait| !wait.is_zero())
+        {
+            let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            let left = match seconds {
+                0..60 => format!("{}s", seconds),
+                _ => format!("{}m {}s", seconds / 60, seconds % 60),
+            };
+            return page(&format!(
+                "You gave an answer too recently; you have to wait after submitting an answer \
+                 before trying again.  You have {} left to wait.",
+                left
+            ));
+        }
+
+        let unlocked = part == 1 || self.solved.contains(&(year, day, 1));
+        let expected = self
+            .answers
+            .get(&year)
+            .and_then(|answers| answers.get(day, part))
+            .filter(|_| unlocked && !self.solved.contains(&(year, day, part)));
+        let Some(expected) = expected else {
+            return page(
+                "You don't seem to be solving the right level.  Did you already complete it?",
+            );
+        };
+
+        let answer = answer
+            .trim()
+            .parse::<Answer>()
+            .unwrap_or_else(|never| match never {});
+        if answer == *expected {
+            self.solved.insert((year, day, part));
+            return page("That's the right answer!  You are one gold star closer to finding the Chief Historian.");
+        }
+
+        self.penalised_until = Some(now + self.penalty);
+        let direction = match (answer.number(), expected.number()) {
+            (Some(answer), Some(expected)) if answer > expected => "; your answer is too high",
+            (Some(_), Some(_)) => "; your answer is too low",
+            _ => "",
+        };
+        page(&format!(
+            "That's not the right answer{}.  If you're stuck, make sure you're using the full \
+             input data.  Please wait one minute before trying again.",
+            direction
+        ))
+    }
+}
+
+/// A stand-in for the puzzle site on a local port, serving fixtures so the client can be
+/// tested without the network. It stops when dropped.
+pub struct Server {
+    address: SocketAddr,
+    fixtures: Arc<Mutex<Fixtures>>,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// Start serving on `address`, where port 0 picks any free port.
+    pub fn start(address: impl ToSocketAddrs, fixtures: Fixtures) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let fixtures = Arc::new(Mutex::new(fixtures));
+        let stopping = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let (fixtures, stopping) = (fixtures.clone(), stopping.clone());
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopping.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    // A client which disconnects or sends nonsense only affects itself.
+                    if let Ok(stream) = stream {
+                        serve(stream, &fixtures).ok();
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            address,
+            fixtures,
+            stopping,
+            thread: Some(thread),
+        })
+    }
+
+    /// The base URL to give the client.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// The fixtures, to inspect the requests received or change what's served.
+    pub fn fixtures(&self) -> MutexGuard<'_, Fixtures> {
+        self.fixtures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        // Wake the listener, which is blocked waiting for a connection.
+        TcpStream::connect(self.address).ok();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, fixtures: &Mutex<Fixtures>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let request = Request::read(&mut stream)?;
+    let (status, headers, body) = fixtures
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .respond(&request);
+
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        _ => "",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+/// The fields of a form as a browser encodes them, ignoring any badly escaped bytes.
+fn parse_form(body: &str) -> Vec<(String, String)> {
+    let decode = |text: &str| {
+        let mut bytes = Vec::new();
+        let mut rest = text.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            rest = tail;
+            match byte {
+                b'+' => bytes.push(b' '),
+                b'%' => {
+                    let escape = rest
+                        .get(..2)
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    if let Some(escape) = escape {
+                        bytes.push(escape);
+                        rest = &rest[2..];
+                    }
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    body.split('&')
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            let (name, value) = field.split_once('=').unwrap_or((field, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The status, `Retry-After` header and body of a response.
+    fn reply(result: Result<ureq::Response, ureq::Error>) -> (u16, Option<String>, String) {
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => panic!("request failed: {}", error),
+        };
+        let retry = response.header("Retry-After").map(str::to_string);
+        (response.status(), retry, response.into_string().unwrap())
+    }
+
+    fn get(server: &Server, path: &str, session: &str) -> (u16, Option<String>, String) {
+        reply(
+            ureq::get(&format!("{}{}", server.url(), path))
+                .set("Cookie", &format!("session={}", session))
+                .call(),
+        )
+    }
+
+    #[test]
+    fn serves_inputs() {
+        let mut fixtures = Fixtures::new("secret");
+        fixtures.inputs.insert((2024, 1), "3   4\n".to_string());
+        let server = Server::start("127.0.0.1:0", fixtures).unwrap();
+
+        let (status, _, body) = get(&server, "/2024/day/1/input", "secret");
+        assert_eq!((status, body.as_str()), (200, "3   4\n"));
+        assert_eq!(get(&server, "/2024/day/1/input", "wrong").0, 400);
+        assert_eq!(get(&server, "/2024/day/2/input", "secret").0, 404);
+        assert_eq!(get(&server, "/2024/day/x/input", "secret").0, 404);
+        assert_eq!(get(&server, "/", "secret").0, 404);
+        assert_eq!(server.fixtures().requests.len(), 5);
+    }
+
+    #[test]
+    fn judges_answers() {
+        let mut fixtures = Fixtures::new("secret");
+        fixtures.answers.insert(
+            2024,
+            Answers::parse("[day1]\npart1 = 11\npart2 = 31\n").unwrap(),
+        );
+        fixtures.penalty = Duration::from_secs(90);
+        let server = Server::start("127.0.0.1:0", fixtures).unwrap();
+        let url = format!("{}/2024/day/1/answer", server.url());
+        let post = |level: &str, answer: &str| {
+            let request = ureq::post(&url).set("Cookie", "session=secret");
+            reply(request.send_form(&[("level", level), ("answer", answer)])).2
+        };
+
+        assert!(post("2", "31").contains("right level"));
+        assert!(post("1", "11").contains("the right answer"));
+        assert!(post("1", "11").contains("right level"));
+        assert!(post("2", "40").contains("too high"));
+        assert!(post("2", "31").contains("You have 1m 30s left to wait"));
+
+        server.fixtures().penalised_until = None;
+        assert!(post("2", "30").contains("too low"));
+        server.fixtures().penalised_until = None;
+        assert!(post("2", "x").contains("not the right answer."));
+        server.fixtures().penalised_until = None;
+        assert!(post("2", "31").contains("the right answer"));
+    }
+
+    #[test]
+    fn throttles() {
+        let mut fixtures = Fixtures::new("secret");
+        fixtures.interval = Duration::from_secs(60);
+        let server = Server::start("127.0.0.1:0", fixtures).unwrap();
+
+        assert_eq!(get(&server, "/", "secret").0, 404);
+        let (status, retry, _) = get(&server, "/", "secret");
+        assert_eq!((status, retry.as_deref()), (429, Some("60")));
+    }
+
+    #[test]
+    fn requests() {
+        let request = "POST / HTTP/1.1\r\ncontent-length: 2\r\n\r\nhi and more";
+        let request = Request::read(request.as_bytes()).unwrap();
+        assert_eq!(request.header("Content-Length"), Some("2"));
+        assert_eq!(request.body, b"hi");
+
+        assert!(Request::read("GET / HTTP/1.1\r\nbad\r\n\r\n".as_bytes()).is_err());
+        assert!(Request::read("GET / HTTP/1.1\r\n".as_bytes()).is_err());
+        assert!(
+            Request::read("GET / HTTP/1.1\r\nContent-Length: 1000000\r\n\r\n".as_bytes()).is_err()
+        );
+    }
+
+    #[test]
+    fn forms() {
+        assert_eq!(
+            parse_form("level=1&answer=a+b%26c%3D%C3%A9"),
+            [
+                ("level".to_string(), "1".to_string()),
+                ("answer".to_string(), "a b&c=é".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_form("x=%zz%4"),
+            [("x".to_string(), "zz4".to_string())]
+        );
+    }
+}
@@ -0,0 +1,567 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    answers::{Answer, Answers},
+    crypt,
+    guesses::{Guess, Guesses, Verdict},
+    parser,
+};
+
+/// Environment variable overriding the puzzle site's base URL, such as a local stand-in's.
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Environment variable holding the session cookie of a logged-in browser. Without it, the
+/// token is read from a `session` file in the input directory.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// The shortest time to leave between requests to the site, shared by every process using
+/// the same input directory.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// How long to wait for the site to connect, and then for each read.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum SiteError {
+    /// Not an `http` or `https` URL.
+    InvalidUrl(String),
+    Http(Box<ureq::Error>),
+    Io(io::Error),
+    NoSession,
+    /// The site didn't accept the session token.
+    Unauthorised,
+    /// The puzzle hasn't unlocked yet, or doesn't exist.
+    Unavailable {
+        year: usize,
+        day: usize,
+    },
+    /// Too many requests, with how long the site asked to wait, if it said.
+    RateLimited(Option<Duration>),
+    /// A guess for a part which is already solved, or whose previous part isn't.
+    WrongLevel {
+        day: usize,
+        part: usize,
+    },
+    Status(u16),
+    /// A response to a guess which didn't say how it was judged.
+    Unrecognised(String),
+}
+
+impl fmt::Display for SiteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SiteError::InvalidUrl(url) => write!(f, "{} is not an http or https URL", url),
+            SiteError::Http(error) => write!(f, "{}", error),
+            SiteError::Io(error) => write!(f, "unable to access the cache: {}", error),
+            SiteError::NoSession => write!(
+                f,
+                "no session token, set {} or write it to {}",
+                SESSION_VAR,
+                session_path().display()
+            ),
+            SiteError::Unauthorised => write!(f, "the session token was not accepted"),
+            SiteError::Unavailable { year, day } => {
+                write!(f, "day {} of {} is not available yet", day, year)
+            }
+            SiteError::RateLimited(Some(wait)) => {
+                write!(f, "rate limited, try again in {}s", wait.as_secs())
+            }
+            SiteError::RateLimited(None) => write!(f, "rate limited, try again later"),
+            SiteError::WrongLevel { day, part } => write!(
+                f,
+                "day {} part {} is already solved, or its previous part isn't",
+                day, part
+            ),
+            SiteError::Status(status) => write!(f, "unexpected response status {}", status),
+            SiteError::Unrecognised(text) => write!(f, "unrecognised response: {}", text),
+        }
+    }
+}
+
+impl Error for SiteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SiteError::Http(error) => Some(error.as_ref()),
+            SiteError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for SiteError {
+    fn from(error: ureq::Error) -> Self {
+        SiteError::Http(Box::new(error))
+    }
+}
+
+impl From<io::Error> for SiteError {
+    fn from(error: io::Error) -> Self {
+        SiteError::Io(error)
+    }
+}
+
+/// Where an input came from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum Fetched {
+    Cached,
+    Downloaded,
+}
+
+/// A guess and how it was judged.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Submission {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Answer,
+    pub verdict: Verdict,
+    /// Whether earlier guesses already showed the verdict, so nothing was sent.
+    pub known: bool,
+}
+
+fn session_path() -> PathBuf {
+    parser::input_dir().join("session")
+}
+
+/// The status and body of a response.
+struct Response {
+    status: u16,
+    body: String,
+}
+
+/// A client for the puzzle site, which caches inputs in a directory laid out like the
+/// input directory and spaces out its requests.
+pub struct Client {
+    base: String,
+    agent: ureq::Agent,
+    session: Option<String>,
+    dir: PathBuf,
+    interval: Duration,
+}
+
+impl Client {
+    pub fn new(base: &str, session: Option<&str>, dir: PathBuf) -> Result<Self, SiteError> {
+        if !(base.starts_with("http://") || base.starts_with("https://")) {
+            return Err(SiteError::InvalidUrl(base.to_string()));
+        }
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout_connect(TIMEOUT)
+            .timeout_read(TIMEOUT)
+            .build();
+        Ok(Self {
+            base: base.trim_end_matches('/').to_string(),
+            agent,
+            session: session.map(|session| session.trim().to_string()),
+            dir,
+            interval: DEFAULT_INTERVAL,
+        })
+    }
+
+    /// A client for `base`, or the environment's base URL, or the real site, with the
+    /// environment's session token, caching into the input directory.
+    pub fn from_env(base: Option<&str>) -> Result<Self, SiteError> {
+        let base = match base {
+            Some(base) => base.to_string(),
+            None => env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+        };
+        let session = env::var(SESSION_VAR)
+            .ok()
+            .or_else(|| fs::read_to_string(session_path()).ok())
+            .filter(|session| !session.trim().is_empty());
+        Self::new(&base, session.as_deref(), parser::input_dir())
+    }
+
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    pub fn input_path(&self, year: usize, day: usize) -> PathBuf {
+        self.dir
+            .join(year.to_string())
+            .join(format!("day{}.txt", day))
+    }
+
+    /// Download a day's input into the cache, unless it's already there, plain or
+    /// encrypted, since inputs never change.
+    pub fn fetch(&self, year: usize, day: usize) -> Result<Fetched, SiteError> {
+        let path = self.input_path(year, day);
+        // An empty input is only a placeholder.
+        let placeholder = fs::metadata(&path).is_ok_and(|metadata| metadata.len() == 0);
+        if (path.try_exists()? && !placeholder) || crypt::encrypted_path(&path).try_exists()? {
+            return Ok(Fetched::Cached);
+        }
+
+        let response = self.send("GET", &format!("/{}/day/{}/input", year, day), None)?;
+        match response.status {
+            200 => (),
+            400 | 401 | 403 => return Err(SiteError::Unauthorised),
+            404 => return Err(SiteError::Unavailable { year, day }),
+            status => return Err(SiteError::Status(status)),
+        }
+
+        // Write alongside and rename, so an interrupted download is never mistaken for a
+        // cached input.
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension("txt.part");
+        fs::write(&partial, response.body)?;
+        fs::rename(&partial, &path)?;
+        Ok(Fetched::Downloaded)
+    }
+
+    /// Submit `answer` for a part, unless earlier `guesses` already show how it would be
+    /// judged. Each verdict from the site is added to `guesses`, and a correct answer to
+    /// `answers`, for the caller to save.
+    pub fn submit(
+        &self,
+        year: usize,
+        day: usize,
+        part: usize,
+        answer: Answer,
+        guesses: &mut Guesses,
+        answers: &mut Answers,
+    ) -> Result<Submission, SiteError> {
+        let (verdict, known) = match guesses.judge(day, part, &answer) {
+            Some(verdict) => (verdict, true),
+            None => {
+                let level = part.to_string();
+                let form = [("level", level.as_str()), ("answer", &answer.to_string())];
+                let response = self.send(
+                    "POST",
+                    &format!("/{}/day/{}/answer", year, day),
+                    Some(&form),
+                )?;
+                match response.status {
+                    200 => (),
+                    400 | 401 | 403 => return Err(SiteError::Unauthorised),
+                    404 => return Err(SiteError::Unavailable { year, day }),
+                    status => return Err(SiteError::Status(status)),
+                }
+
+                let verdict = verdict(&response.body, day, part)?;
+                guesses.record(
+                    day,
+                    part,
+                    Guess {
+                        answer: answer.clone(),
+                        verdict,
+                    },
+                );
+                (verdict, false)
+            }
+        };
+
+        if verdict == Verdict::Correct {
+            answers.record(day, part, answer.clone());
+        }
+        Ok(Submission {
+            day,
+            part,
+            answer,
+            verdict,
+            known,
+        })
+    }
+
+    /// Send a request, with `form` as its body if given, once the interval since the last
+    /// has passed, failing if the site says it has had too many.
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        form: Option<&[(&str, &str)]>,
+    ) -> Result<Response, SiteError> {
+        let session = self.session.as_deref().ok_or(SiteError::NoSession)?;
+        self.throttle()?;
+
+        let request = self
+            .agent
+            .request(method, &format!("{}{}", self.base, path))
+            .set("Cookie", &format!("session={}", session));
+        let result = match form {
+            Some(form) => request.send_form(form),
+            None => request.call(),
+        };
+        // Statuses are the caller's to interpret, not errors.
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => return Err(error.into()),
+        };
+
+        let status = response.status();
+        if status == 429 {
+            let wait = response
+                .header("Retry-After")
+                .and_then(|seconds| seconds.parse().ok())
+                .map(Duration::from_secs);
+            return Err(SiteError::RateLimited(wait));
+        }
+        let body = response.into_string()?;
+        Ok(Response { status, body })
+    }
+
+    /// Wait until `interval` after the last request from any process sharing the cache,
+    /// then note this one.
+    fn throttle(&self) -> Result<(), SiteError> {
+        let stamp = self.dir.join(".last-request");
+        let now = || {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+        };
+        let last = fs::read_to_string(&stamp)
+            .ok()
+            .and_then(|millis| millis.trim().parse().ok())
+            .map(Duration::from_millis);
+        if let Some(last) = last {
+            // A stamp in the future, from a clock change, still only costs one interval.
+            let wait = last.saturating_add(self.interval).saturating_sub(now());
+            thread::sleep(wait.min(self.interval));
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        // Round up, so the next request never waits less than the interval.
+        let millis = now().as_micros().div_ceil(1000);
+        fs::write(&stamp, millis.to_string())?;
+        Ok(())
+    }
+}
+
+/// How the site judged a guess, from the wording of its response.
+fn verdict(body: &str, day: usize, part: usize) -> Result<Verdict, SiteError> {
+    lazy_static! {
+        static ref WAIT: Regex = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait")
+            .expect("Unable to initialise regex");
+    }
+
+    if body.contains("That's the right answer") {
+        Ok(Verdict::Correct)
+    } else if body.contains("not the right answer") {
+        Ok(if body.contains("too high") {
+            Verdict::TooHigh
+        } else if body.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        })
+    } else if body.contains("answer too recently") {
+        let wait = WAIT.captures(body).map(|captures| {
+            let number = |index| {
+                captures
+                    .get(index)
+                    .and_then(|number| number.as_str().parse().ok())
+                    .unwrap_or(0)
+            };
+            Duration::from_secs(number(1) * 60 + number(2))
+        });
+        Err(SiteError::RateLimited(wait))
+    } else if body.contains("right level") {
+        Err(SiteError::WrongLevel { day, part })
+    } else {
+        let excerpt = body.chars().take(200).collect();
+        Err(SiteError::Unrecognised(excerpt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::server::{Fixtures, Server};
+
+    fn server() -> Server {
+        let mut fixtures = Fixtures::new("secret");
+        fixtures
+            .inputs
+            .insert((2024, 1), "3   4\n4   3\n".to_string());
+        fixtures.inputs.insert((2024, 2), "7 6 4 2 1\n".to_string());
+        let answers = Answers::parse("[day1]\npart1 = 11\npart2 = 31\n").unwrap();
+        fixtures.answers.insert(2024, answers);
+        Server::start("127.0.0.1:0", fixtures).unwrap()
+    }
+
+    fn client(server: &Server, session: Option<&str>) -> Client {
+        Client::new(&server.url(), session, parser::scratch_dir("site"))
+            .unwrap()
+            .with_interval(Duration::ZERO)
+    }
+
+    #[test]
+    fn fetch_once() {
+        let server = server();
+        let client = client(&server, Some("secret"));
+
+        // An empty placeholder, as `new-day` leaves, is replaced.
+        fs::create_dir_all(client.dir.join("2024")).unwrap();
+        fs::write(client.input_path(2024, 1), "").unwrap();
+        assert_eq!(client.fetch(2024, 1).unwrap(), Fetched::Downloaded);
+        assert_eq!(
+            fs::read_to_string(client.input_path(2024, 1)).unwrap(),
+            "3   4\n4   3\n"
+        );
+        assert_eq!(client.fetch(2024, 1).unwrap(), Fetched::Cached);
+        assert_eq!(server.fixtures().requests, ["GET /2024/day/1/input"]);
+    }
+
+    #[test]
+    fn fetch_failures() {
+        let server = server();
+        assert!(matches!(
+            client(&server, None).fetch(2024, 1),
+            Err(SiteError::NoSession)
+        ));
+        assert!(matches!(
+            client(&server, Some("wrong")).fetch(2024, 1),
+            Err(SiteError::Unauthorised)
+        ));
+
+        let client = client(&server, Some("secret"));
+        assert!(matches!(
+            client.fetch(2024, 25),
+            Err(SiteError::Unavailable {
+                year: 2024,
+                day: 25
+            })
+        ));
+        assert!(!client.input_path(2024, 25).exists());
+
+        assert!(Client::new(DEFAULT_BASE_URL, None, client.dir.clone()).is_ok());
+        assert!(matches!(
+            Client::new("ftp://adventofcode.com", None, client.dir.clone()),
+            Err(SiteError::InvalidUrl(_))
+        ));
+
+        // Nothing listening is a transport error, not a status.
+        let address = server.url();
+        drop(server);
+        let client = Client::new(&address, Some("secret"), client.dir.clone())
+            .unwrap()
+            .with_interval(Duration::ZERO);
+        assert!(matches!(client.fetch(2024, 1), Err(SiteError::Http(_))));
+    }
+
+    #[test]
+    fn rate_limits() {
+        let server = server();
+        server.fixtures().interval = Duration::from_millis(200);
+
+        // Waiting between requests keeps the site happy.
+        let client = client(&server, Some("secret")).with_interval(Duration::from_millis(250));
+        let start = Instant::now();
+        client.fetch(2024, 1).unwrap();
+        client.fetch(2024, 2).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(250));
+
+        // Rushing is refused, and says for how long.
+        server.fixtures().interval = Duration::from_secs(60);
+        let impatient = client.with_interval(Duration::ZERO);
+        fs::remove_file(impatient.input_path(2024, 1)).unwrap();
+        assert!(matches!(
+            impatient.fetch(2024, 1),
+            Err(SiteError::RateLimited(Some(wait))) if wait == Duration::from_secs(60)
+        ));
+        assert!(!impatient.input_path(2024, 1).exists());
+    }
+
+    #[test]
+    fn verdicts() {
+        let judge = |text: &str| verdict(&format!("<article><p>{}</p></article>", text), 1, 2);
+        assert!(matches!(
+            judge("That's the right answer!  You are one gold star closer."),
+            Ok(Verdict::Correct)
+        ));
+        assert!(matches!(
+            judge("That's not the right answer; your answer is too high.  Please wait."),
+            Ok(Verdict::TooHigh)
+        ));
+        assert!(matches!(
+            judge("That's not the right answer; your answer is too low."),
+            Ok(Verdict::TooLow)
+        ));
+        assert!(matches!(
+            judge("That's not the right answer.  If you're stuck, ..."),
+            Ok(Verdict::Wrong)
+        ));
+        assert!(matches!(
+            judge("You gave an answer too recently.  You have 1m 5s left to wait."),
+            Err(SiteError::RateLimited(Some(wait))) if wait == Duration::from_secs(65)
+        ));
+        assert!(matches!(
+            judge("You gave an answer too recently.  You have 42s left to wait."),
+            Err(SiteError::RateLimited(Some(wait))) if wait == Duration::from_secs(42)
+        ));
+        assert!(matches!(
+            judge("You don't seem to be solving the right level.  Did you already complete it?"),
+            Err(SiteError::WrongLevel { day: 1, part: 2 })
+        ));
+        assert!(matches!(
+            judge("Something else"),
+            Err(SiteError::Unrecognised(_))
+        ));
+    }
+
+    #[test]
+    fn submit_once() {
+        let server = server();
+        let client = client(&server, Some("secret"));
+        let (mut guesses, mut answers) = (Guesses::default(), Answers::default());
+        let mut submit = |answer: i64| {
+            client
+                .submit(2024, 1, 1, answer.into(), &mut guesses, &mut answers)
+                .map(|submission| (submission.verdict, submission.known))
+        };
+
+        assert!(matches!(submit(20), Ok((Verdict::TooHigh, false))));
+        assert!(matches!(submit(25), Ok((Verdict::TooHigh, true))));
+        assert!(matches!(submit(20), Ok((Verdict::TooHigh, true))));
+        assert!(matches!(submit(5), Ok((Verdict::TooLow, false))));
+        assert!(matches!(submit(2), Ok((Verdict::TooLow, true))));
+        assert!(matches!(submit(11), Ok((Verdict::Correct, false))));
+        assert!(matches!(submit(11), Ok((Verdict::Correct, true))));
+        assert!(matches!(submit(12), Ok((Verdict::Wrong, true))));
+
+        assert_eq!(server.fixtures().requests.len(), 3);
+        assert_eq!(guesses.get(1, 1).len(), 3);
+        assert_eq!(answers.get(1, 1), Some(&Answer::Integer(11)));
+    }
+
+    #[test]
+    fn submit_too_soon() {
+        let server = server();
+        server.fixtures().penalty = Duration::from_secs(60);
+        let client = client(&server, Some("secret"));
+        let (mut guesses, mut answers) = (Guesses::default(), Answers::default());
+        assert!(matches!(
+            client.submit(2024, 1, 2, 31.into(), &mut guesses, &mut answers),
+            Err(SiteError::WrongLevel { day: 1, part: 2 })
+        ));
+
+        let submission = client
+            .submit(2024, 1, 1, 10.into(), &mut guesses, &mut answers)
+            .unwrap();
+        assert_eq!(submission.verdict, Verdict::TooLow);
+        assert!(matches!(
+            client.submit(2024, 1, 1, 11.into(), &mut guesses, &mut answers),
+            Err(SiteError::RateLimited(Some(_)))
+        ));
+        assert_eq!(guesses.get(1, 1).len(), 1);
+        assert_eq!(answers.get(1, 1), None);
+    }
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    animation::Recording, answers::Answer, parser::ParseError, random::Rng, registry,
+    render::Canvas,
+};
+
+/// A day's puzzle: how to parse its input and solve each part.
+pub trait Solution {
+    const DAY: usize;
+    type Input;
+    type Answer: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Answer;
+    fn part2(input: &Self::Input) -> Self::Answer;
+}
+
+/// What the command line and tests can do with a day beyond solving it. Kept apart from
+/// [`Solution`], so the library's API is just parsing and solving.
+pub(crate) trait Extras: Solution {
+    /// Draw the interesting state of the puzzle, for days which have a grid to show.
+    fn render(_input: &Self::Input) -> Option<Canvas> {
+        None
+    }
+
+    /// Record each step of the puzzle's simulation, for days which have one to watch.
+    /// Returns whether anything was recorded.
+    fn animate(_input: &Self::Input, _recording: &mut Recording) -> bool {
+        false
+    }
+
+    /// A random valid input whose size grows with `size`, for days which can make them up.
+    fn generate(_rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    UnknownDay(usize),
+    UnknownPart { day: usize, part: usize },
+    Parse(ParseError),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SolveError::UnknownDay(day) => write!(f, "day {} is not solved", day),
+            SolveError::UnknownPart { day, part } => {
+                write!(f, "day {} part {} is not solved", day, part)
+            }
+            SolveError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> Self {
+        SolveError::Parse(error)
+    }
+}
+
+/// Solve one part of a day from its raw input, without knowing the day's types.
+pub fn solve(day: usize, part: usize, input: &str) -> Result<Answer, SolveError> {
+    let registered = registry::day(day).ok_or(SolveError::UnknownDay(day))?;
+    let runner = registered
+        .part(part)
+        .ok_or(SolveError::UnknownPart { day, part })?;
+    Ok(runner(input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day7::Day7;
+
+    const TEST: &str = "
+        190: 10 19
+        3267: 81 40 27
+    ";
+
+    #[test]
+    fn typed() {
+        let input = Day7::parse(TEST).unwrap();
+        assert_eq!(Day7::part1(&input), 3457);
+        assert_eq!(Day7::DAY, 7);
+    }
+
+    #[test]
+    fn erased() {
+        assert_eq!(solve(7, 1, TEST), Ok(Answer::Integer(3457)));
+        assert_eq!(solve(26, 1, TEST), Err(SolveError::UnknownDay(26)));
+        assert_eq!(
+            solve(9, 2, "12345"),
+            Err(SolveError::UnknownPart { day: 9, part: 2 })
+        );
+        assert!(matches!(solve(7, 1, "x"), Err(SolveError::Parse(_))));
+    }
+}